@@ -0,0 +1,178 @@
+//! Measures how fast this machine searches, so the runtime of a crack can be predicted
+
+use std::fmt;
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::{CrackProgress, CANCEL_INTERVAL, SEARCH_SPACE, WORLD_SEED};
+use crate::estimate::DEFAULT_SEEDS_PER_SECOND;
+use crate::generator::BedrockGenerator;
+use crate::layer::create_filter_tree;
+use crate::raw_data::block::Block;
+use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+use crate::raw_data::sender::Sender;
+
+/// Search speed of this machine for some thread counts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Calibration {
+    /// thread count and seeds per second of all threads together, sorted by thread count
+    pub measurements: Vec<(u64, f64)>,
+}
+
+impl Calibration {
+    /// Speed of all threads together. Thread counts that werent measured scale the closest measurement,
+    /// without any measurement DEFAULT_SEEDS_PER_SECOND is used
+    pub fn seeds_per_second(&self, thread_count: u64) -> f64 {
+        let thread_count = thread_count.max(1);
+        self.measurements
+            .iter()
+            .min_by_key(|(threads, _)| threads.abs_diff(thread_count))
+            .map_or(DEFAULT_SEEDS_PER_SECOND * thread_count as f64, |(threads, speed)| {
+                speed / *threads as f64 * thread_count as f64
+            })
+    }
+
+    /// Reads the format written by Display, one "threads seeds_per_second" pair per line
+    pub fn parse(calibration: &str) -> Result<Calibration, String> {
+        let mut measurements = calibration
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let mut parts = line.split_whitespace();
+                let threads = parts.next().and_then(|threads| threads.parse::<u64>().ok());
+                let speed = parts.next().and_then(|speed| speed.parse::<f64>().ok());
+                match (threads, speed, parts.next()) {
+                    (Some(threads), Some(speed), None) if threads > 0 && speed > 0.0 => Ok((threads, speed)),
+                    _ => Err(format!("line {}: expected a thread count and seeds per second: {}", i + 1, line.trim())),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        measurements.sort_by_key(|(threads, _)| *threads);
+        Ok(Calibration { measurements })
+    }
+}
+
+impl fmt::Display for Calibration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (threads, speed) in &self.measurements {
+            writeln!(f, "{threads} {speed:.0}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+struct Discard;
+
+impl Sender for Discard {
+    fn send(&self, _progress: CrackProgress) -> bool {
+        true
+    }
+}
+
+/// the blocks of a typical crack, estimate_crack scales the measured speed to the blocks of a crack
+pub(crate) fn benchmark_blocks() -> Vec<Block> {
+    BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal)
+        .generate_area(0, 0, 5, 5)
+        .into_iter()
+        .filter(|block| (123..=126).contains(&block.y) || block.y == 4)
+        .collect()
+}
+
+/// Runs the filter tree on thread_count threads for about duration, returns seeds per second
+pub fn benchmark(thread_count: u64, duration: Duration) -> f64 {
+    benchmark_blocks_speed(&benchmark_blocks(), thread_count, duration)
+}
+
+/// seeds per second of the filter tree of blocks
+fn benchmark_blocks_speed(blocks: &[Block], thread_count: u64, duration: Duration) -> f64 {
+    let thread_count = thread_count.max(1);
+    let checks = create_filter_tree(blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, Discard);
+    let start = Instant::now();
+    let searched: u64 = thread::scope(|scope| {
+        let threads: Vec<_> = (0..thread_count)
+            .map(|thread| {
+                let checks = &checks;
+                scope.spawn(move || {
+                    // every thread gets its own part of the search space, like a real crack
+                    let first = (thread * (SEARCH_SPACE.end / thread_count)) & !(CANCEL_INTERVAL - 1);
+                    let mut searched = 0;
+                    for upper_bits in (first..SEARCH_SPACE.end).step_by(1 << 12) {
+                        if upper_bits % CANCEL_INTERVAL == 0 && start.elapsed() >= duration {
+                            break;
+                        }
+                        checks.run_checks(upper_bits);
+                        searched += 1 << 12;
+                    }
+                    searched
+                })
+            })
+            .collect();
+        threads.into_iter().map(|thread| thread.join().unwrap()).sum()
+    });
+    searched as f64 / start.elapsed().as_secs_f64()
+}
+
+/// How many groups of 8 checks a seed that reaches the comparison of the surfaces costs, see search_work.
+/// Benchmarks the benchmark blocks, which mostly run checks, against a few common blocks that let
+/// most seeds through to the comparison, and solves for the cost that makes both take the same work per second
+#[cfg(test)]
+fn measure_cross_comparison_cost(duration: Duration) -> f64 {
+    use crate::estimate::search_work;
+    use crate::raw_data::block_type::BlockType;
+
+    let common: Vec<_> = (0..10).map(|x| Block::new(x, 4, 0, BlockType::OTHER)).collect();
+    let work = |blocks: &[Block]| search_work(blocks, CrackerMode::Normal, BedrockProfile::NETHER, 0);
+    let ((checks_a, compared_a), (checks_b, compared_b)) = (work(&benchmark_blocks()), work(&common));
+    let speed_a = benchmark_blocks_speed(&benchmark_blocks(), 1, duration);
+    let speed_b = benchmark_blocks_speed(&common, 1, duration);
+    // speed_a * (checks_a + compared_a * cost) = speed_b * (checks_b + compared_b * cost)
+    (speed_b * checks_b - speed_a * checks_a) / (speed_a * compared_a - speed_b * compared_b)
+}
+
+/// Benchmarks every thread count for about duration
+pub fn calibrate(thread_counts: &[u64], duration: Duration) -> Calibration {
+    let mut thread_counts = thread_counts.to_vec();
+    thread_counts.sort_unstable();
+    thread_counts.dedup();
+    Calibration {
+        measurements: thread_counts
+            .into_iter()
+            .map(|threads| (threads, benchmark(threads, duration)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::estimate::CROSS_COMPARISON_COST;
+    use super::*;
+
+    #[test]
+    fn test_parse_calibration() {
+        let calibration = Calibration { measurements: vec![(1, 1.0e11), (8, 6.0e11)] };
+        assert_eq!(Calibration::parse(&calibration.to_string()).unwrap(), calibration);
+        assert_eq!(calibration.seeds_per_second(1), 1.0e11);
+        assert_eq!(calibration.seeds_per_second(4), 4.0e11);
+        assert_eq!(calibration.seeds_per_second(16), 12.0e11);
+        assert_eq!(Calibration::default().seeds_per_second(2), 2.0 * DEFAULT_SEEDS_PER_SECOND);
+        assert!(Calibration::parse("1 fast").is_err());
+        assert!(Calibration::parse("0 100").is_err());
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let calibration = calibrate(&[2, 1, 2], Duration::from_millis(50));
+        let threads: Vec<_> = calibration.measurements.iter().map(|(threads, _)| *threads).collect();
+        assert_eq!(threads, vec![1, 2]);
+        assert!(calibration.measurements.iter().all(|(_, speed)| *speed > 0.0));
+    }
+
+    #[test]
+    fn test_cross_comparison_cost() {
+        // timing depends on the machine and the other tests, only a wrong order of magnitude fails
+        let cost = measure_cross_comparison_cost(Duration::from_millis(300));
+        assert!(cost > CROSS_COMPARISON_COST / 2.0 && cost < CROSS_COMPARISON_COST * 2.0, "measured {cost}");
+    }
+}
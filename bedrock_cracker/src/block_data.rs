@@ -1,8 +1,10 @@
+use std::collections::HashMap;
 use java_random::JAVA_LCG;
 use crate::{MASK48};
 use crate::raw_data::block::Block;
 use crate::raw_data::block_type::BlockType;
 use crate::raw_data::modes::CrackerMode;
+use crate::raw_data::profile::BedrockProfile;
 
 #[derive(Clone, Debug)]
 pub struct BlockFilter {
@@ -13,16 +15,13 @@ pub struct BlockFilter {
 }
 
 impl BlockFilter {
-    pub fn from(b: &Block, mode: CrackerMode) -> BlockFilter {
-        Self::new(b.x, b.y, b.z, b.block_type, mode)
+    pub fn from(b: &Block, mode: CrackerMode, profile: BedrockProfile) -> BlockFilter {
+        Self::new(b.x, b.y, b.z, b.block_type, mode, profile)
     }
 
-    fn new(x: i32, mut y: i32, z: i32, block_type: BlockType, mode: CrackerMode) -> Self {
-        let (lower_bound, upper_bound) = Self::bounds(y, block_type);
-        if mode == CrackerMode::Paper1_18 {
-            y = if y > 5 { 122 } else { 0 }
-        }
-        let pos_hash = BlockFilter::hashcode(x, y, z) ^ JAVA_LCG.multiplier;
+    fn new(x: i32, y: i32, z: i32, block_type: BlockType, mode: CrackerMode, profile: BedrockProfile) -> Self {
+        let (lower_bound, upper_bound) = Self::bounds(y, block_type, profile);
+        let pos_hash = BlockFilter::hashcode(x, Self::random_y(y, mode, profile), z) ^ JAVA_LCG.multiplier;
 
         Self {
             pos_hash,
@@ -55,6 +54,13 @@ impl BlockFilter {
         fail_chance * (1 << lower_bits) as f64
     }
 
+    /// chance that a random seed passes the check of a layer with lower_bits unknown bits
+    pub(crate) fn pass_chance(&self, lower_bits: u64) -> f64 {
+        let lower_bits_mask = (1 << lower_bits) - 1;
+        let bound = self.bound() + lower_bits_mask * JAVA_LCG.multiplier;
+        (bound as f64 / MASK48 as f64).min(1.0)
+    }
+
     //the chance for new info decreases
     fn check_with_bits(&mut self, lower_bits: u64) {
         let lower_bits_mask = (1 << lower_bits) - 1;
@@ -64,12 +70,30 @@ impl BlockFilter {
         self.possible_range = new_range;
     }
 
+    /// blocks with the same hash use the same random
+    pub(crate) fn pos_hash(&self) -> u64 {
+        self.pos_hash
+    }
+
+    /// the random values that match the block, scaled to 48 bits
+    pub(crate) fn pass_range(&self) -> (u64, u64) {
+        (self.lower_bound, self.upper_bound)
+    }
+
+    /// 0 for blocks that cant exist, like other blocks on y=0
     fn bound(&self) -> u64 {
-        assert!(self.upper_bound > self.lower_bound);
-        self.upper_bound - self.lower_bound
+        self.upper_bound.saturating_sub(self.lower_bound)
+    }
+
+    /// paper used the bottom of the gradient as y for every block in a column
+    pub(crate) fn random_y(y: i32, mode: CrackerMode, profile: BedrockProfile) -> i32 {
+        match mode {
+            CrackerMode::Normal => y,
+            CrackerMode::Paper1_18 => profile.paper_y(y),
+        }
     }
 
-    fn hashcode(x: i32, y: i32, z: i32) -> u64 {
+    pub(crate) fn hashcode(x: i32, y: i32, z: i32) -> u64 {
         let mut pos_hash =
             (x.wrapping_mul(3129871)) as i64 ^ ((z as i64).wrapping_mul(116129781)) ^ y as i64;
         pos_hash = pos_hash
@@ -80,22 +104,20 @@ impl BlockFilter {
         pos_hash >> 16
     }
 
-    fn bounds(mut layer: i32, block_type: BlockType) -> (u64, u64) {
+    fn bounds(layer: i32, block_type: BlockType, profile: BedrockProfile) -> (u64, u64) {
         let mut lower_bound = 0.0;
         let mut upper_bound = 1.0;
 
-        if layer > 5 {
-            layer -= 122;
-            let bound = (5 - layer) as f64 / 5.0;
+        let bound = profile.gradient(layer);
+        if !profile.is_floor(layer) {
             match block_type {
                 BlockType::BEDROCK => lower_bound = bound,
-                BlockType::OTHER => upper_bound = bound,
+                BlockType::OTHER | BlockType::DEEPSLATE => upper_bound = bound,
             }
         } else {
-            let bound = (5 - layer) as f64 / 5.0;
             match block_type {
                 BlockType::BEDROCK => upper_bound = bound,
-                BlockType::OTHER => lower_bound = bound,
+                BlockType::OTHER | BlockType::DEEPSLATE => lower_bound = bound,
             }
         }
 
@@ -116,6 +138,15 @@ pub struct CheckObject {
 }
 
 impl CheckObject {
+    /// fails for every seed
+    pub(crate) fn failing() -> Self {
+        Self {
+            pos_hash: 0,
+            condition: 1 << 48,
+            offset: 0,
+        }
+    }
+
     fn new(pos_hash: u64, lower_bound: u64, upper_bound: u64, lower_bit_mask: u64) -> Self {
         let offset = MASK48 - upper_bound;
         let pos_hash = pos_hash & (MASK48 - lower_bit_mask);
@@ -149,6 +180,19 @@ pub fn get_filter_power(filters: &[BlockFilter]) -> u64 {
     resulting_seeds as u64
 }
 
+/// The random values every group of blocks with the same random can still have,
+/// keyed by the surface and the position hash. Fixed layers tell nothing and are left out
+pub(crate) fn known_ranges(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile) -> HashMap<(bool, u64), (u64, u64)> {
+    let mut known: HashMap<(bool, u64), (u64, u64)> = HashMap::new();
+    for block in blocks.iter().filter(|block| profile.fixed_block(block.y).is_none()) {
+        let filter = BlockFilter::from(block, mode, profile);
+        let (lower, upper) = filter.pass_range();
+        let range = known.entry((profile.is_floor(block.y), filter.pos_hash())).or_insert((0, MASK48));
+        *range = (range.0.max(lower), range.1.min(upper));
+    }
+    known
+}
+
 #[cfg(test)]
 mod tests {
     use java_random::JAVA_LCG;
@@ -156,15 +200,22 @@ mod tests {
     use crate::MASK48;
     use crate::raw_data::block_type::BlockType;
     use crate::raw_data::modes::CrackerMode;
+    use crate::raw_data::profile::BedrockProfile;
 
     #[test]
     fn test_hashcode() {
-        let block = BlockFilter::new(-98, 4, -469, BlockType::BEDROCK, CrackerMode::Normal);
+        let block = BlockFilter::new(-98, 4, -469, BlockType::BEDROCK, CrackerMode::Normal, BedrockProfile::NETHER);
         assert_eq!(block.pos_hash, 99261249361405 ^ JAVA_LCG.multiplier)
     }
 
     #[test]
     fn test_filler_check() {
-        assert!(!CheckObject::filler().check(MASK48))
+        assert!(!CheckObject::default().check(MASK48))
+    }
+
+    #[test]
+    fn test_failing_check() {
+        assert!(CheckObject::failing().check(MASK48));
+        assert!(CheckObject::failing().check(0));
     }
 }
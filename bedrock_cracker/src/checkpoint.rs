@@ -0,0 +1,202 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::raw_data::block::Block;
+use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+
+const HEADER: &str = "# bedrock cracker checkpoint";
+
+/// Remembers which chunks of the search range are done so a search can be resumed.
+/// Seeds of finished chunks were already reported and wont be reported again
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    path: PathBuf,
+    params: u64,
+    range: Range<u64>,
+    finished: Vec<bool>,
+}
+
+impl Checkpoint {
+    /// Loads the checkpoint at path or starts a new one if the file doesnt exist.
+    /// range are the upper bits that get searched, crate::SEARCH_SPACE or a shard_range.
+    /// Fails if the checkpoint belongs to a search with different parameters
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        blocks: &[Block],
+        mode: CrackerMode,
+        profile: BedrockProfile,
+        output: OutputMode,
+        tolerance: u32,
+        range: Range<u64>,
+    ) -> io::Result<Checkpoint> {
+        let path = path.as_ref().to_path_buf();
+        let params = fingerprint(blocks, mode, profile, output, tolerance, &range);
+        let mut checkpoint = Checkpoint {
+            path,
+            params,
+            range,
+            finished: vec![false; crate::CHUNK_COUNT as usize],
+        };
+
+        match fs::read_to_string(&checkpoint.path) {
+            Ok(content) => checkpoint.parse(&content)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        Ok(checkpoint)
+    }
+
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    pub fn finished_chunks(&self) -> u64 {
+        self.finished.iter().filter(|finished| **finished).count() as u64
+    }
+
+    pub fn is_complete(&self) -> bool {
+        let first_chunk = self.range.start / crate::CHUNK_SIZE;
+        let last_chunk = self.range.end.div_ceil(crate::CHUNK_SIZE);
+        self.finished[first_chunk as usize..last_chunk as usize]
+            .iter()
+            .all(|finished| *finished)
+    }
+
+    pub fn is_finished(&self, chunk: u64) -> bool {
+        self.finished[chunk as usize]
+    }
+
+    /// Marks a chunk as done and writes the checkpoint to disk.
+    /// Call it for CrackProgress::ChunkDone once the seeds before it are saved,
+    /// a chunk marked too early loses its seeds when the crack gets interrupted
+    pub fn finish(&mut self, chunk: u64) -> io::Result<()> {
+        self.finished[chunk as usize] = true;
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        // write to a temporary file first so a crash never leaves a broken checkpoint behind
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, self.to_string())?;
+        fs::rename(&tmp, &self.path)
+    }
+
+    fn parse(&mut self, content: &str) -> io::Result<()> {
+        let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {msg}", self.path.display()));
+
+        let mut lines = content.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(invalid("not a checkpoint file"));
+        }
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "params" => {
+                    if u64::from_str_radix(value, 16).ok() != Some(self.params) {
+                        return Err(invalid("checkpoint belongs to a different block list or settings"));
+                    }
+                }
+                "chunks" => {
+                    if value.parse::<u64>().ok() != Some(crate::CHUNK_COUNT) {
+                        return Err(invalid("checkpoint uses a different chunk size"));
+                    }
+                }
+                "finished" => {
+                    for range in value.split_whitespace() {
+                        let (start, end) = range.split_once('-').unwrap_or((range, range));
+                        let (start, end) = match (start.parse::<usize>(), end.parse::<usize>()) {
+                            (Ok(start), Ok(end)) if start <= end && end < self.finished.len() => (start, end),
+                            _ => return Err(invalid("invalid chunk range")),
+                        };
+                        self.finished[start..=end].fill(true);
+                    }
+                }
+                _ => return Err(invalid("unknown entry")),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{HEADER}\nparams {:016x}\nchunks {}\nfinished", self.params, crate::CHUNK_COUNT)?;
+        let mut chunk = 0;
+        while chunk < self.finished.len() {
+            if !self.finished[chunk] {
+                chunk += 1;
+                continue;
+            }
+            let start = chunk;
+            while chunk + 1 < self.finished.len() && self.finished[chunk + 1] {
+                chunk += 1;
+            }
+            if start == chunk {
+                write!(f, " {start}")?;
+            } else {
+                write!(f, " {start}-{chunk}")?;
+            }
+            chunk += 1;
+        }
+        writeln!(f)
+    }
+}
+
+/// FNV-1a over everything that changes the result of a search
+fn fingerprint(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32, range: &Range<u64>) -> u64 {
+    let mut blocks: Vec<String> = blocks.iter().map(|block| block.to_string()).collect();
+    blocks.sort();
+    let params = format!("{}|{:?}|{:?}|{:?}|{}|{:?}", blocks.join(";"), mode, profile, output, tolerance, range);
+
+    params.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use crate::raw_data::block_type::BlockType;
+    use super::*;
+
+    const BLOCKS: [Block; 2] = [
+        Block::new(19, 123, -117, BlockType::BEDROCK),
+        Block::new(10, 1, -111, BlockType::OTHER),
+    ];
+
+    fn open(path: &Path, tolerance: u32) -> io::Result<Checkpoint> {
+        Checkpoint::open(path, &BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, tolerance, crate::SEARCH_SPACE)
+    }
+
+    #[test]
+    fn test_save_and_resume() {
+        let path = env::temp_dir().join(format!("bedrock_checkpoint_{}", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let mut checkpoint = open(&path, 0).unwrap();
+        assert_eq!(checkpoint.finished_chunks(), 0);
+        for chunk in [0, 1, 2, 7, 2047] {
+            checkpoint.finish(chunk).unwrap();
+        }
+        assert!(fs::read_to_string(&path).unwrap().ends_with("finished 0-2 7 2047\n"));
+
+        let resumed = open(&path, 0).unwrap();
+        assert_eq!(resumed.finished, checkpoint.finished);
+        assert!(resumed.is_finished(7) && !resumed.is_finished(8));
+
+        // different settings must not reuse the checkpoint
+        assert!(open(&path, 1).is_err());
+        let shard = crate::shard_range(0, 4);
+        assert!(Checkpoint::open(&path, &BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, shard).is_err());
+        let profile = BedrockProfile { roof_y: 255, ..BedrockProfile::NETHER };
+        assert!(Checkpoint::open(&path, &BLOCKS, CrackerMode::Normal, profile, OutputMode::WorldSeed, 0, crate::SEARCH_SPACE).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
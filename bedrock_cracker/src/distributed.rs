@@ -0,0 +1,386 @@
+//! Splits a nether search over several machines.
+//!
+//! The coordinator hands out ranges of upper bits to workers over a line based TCP protocol.
+//! A range only counts as searched once the worker finished it, a range of a worker that
+//! disconnects or stops responding is given to another worker. Seeds are reported right away
+//! and the coordinator skips the ones it already reported, so searching a range twice doesnt
+//! duplicate them.
+//!
+//! The protocol isnt encrypted and only the shared token keeps others out, run it on a trusted network.
+//!
+//! coordinator -> worker: `SETUP <mode> <output> <tolerance> <floor y> <roof y> <depth> <floor hash> <roof hash> <block count>`,
+//! one line per block,
+//! then `RANGE <start> <end>` for every range and `FINISHED` once everything is searched.
+//! worker -> coordinator: `HELLO <threads> [token]`, then `SEED <seed>`, `PARTIAL <seed> <mismatches>`,
+//! `PROGRESS <amount>` and `ALIVE` while searching a range and `DONE` after it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::ops::Range;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::{search_bedrock_range, shard_range, CrackProgress};
+use crate::handle::{CrackHandle, SearchState};
+use crate::raw_data::block::Block;
+use crate::raw_data::config::{parse_line, ConfigError};
+use crate::raw_data::modes::{CrackerMode, Dimension, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+use crate::raw_data::sender::Sender;
+
+/// amount of ranges the search space is split into
+const WORK_UNITS: u64 = 512;
+/// a worker that doesnt report anything for this long is considered dead
+const WORKER_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// how often a worker tells that it is alive while it has nothing else to report
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Default)]
+struct WorkQueue {
+    pending: VecDeque<Range<u64>>,
+    in_progress: usize,
+    stopped: bool,
+    /// seeds and their mismatches that were sent already, by the start of their range.
+    /// only a range that a worker gave back is searched twice, so a done range forgets its seeds
+    reported: HashMap<u64, HashSet<(u64, u32)>>,
+}
+
+impl WorkQueue {
+    fn is_done(&self) -> bool {
+        self.stopped || (self.pending.is_empty() && self.in_progress == 0)
+    }
+}
+
+type SharedQueue = Arc<Mutex<WorkQueue>>;
+
+/// Waits for a free range, None once every range is searched
+fn next_range(queue: &SharedQueue) -> Option<Range<u64>> {
+    loop {
+        {
+            let mut queue = queue.lock().unwrap();
+            if queue.is_done() {
+                return None;
+            }
+            if let Some(range) = queue.pending.pop_front() {
+                queue.in_progress += 1;
+                return Some(range);
+            }
+        }
+        // another worker might still fail and give its range back
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Accepts workers on the listener and reports their results to the sender.
+/// Workers have to send the same token, an empty token accepts every worker.
+/// Returns immediately, cancelling the handle stops handing out ranges
+#[allow(clippy::too_many_arguments)]
+pub fn run_coordinator<S: Sender + 'static>(
+    listener: TcpListener,
+    blocks: &[Block],
+    mode: CrackerMode,
+    profile: BedrockProfile,
+    output: OutputMode,
+    tolerance: u32,
+    token: &str,
+    sender: S,
+) -> io::Result<CrackHandle> {
+    let ranges = (0..WORK_UNITS).map(|unit| shard_range(unit, WORK_UNITS)).collect();
+    coordinate(listener, blocks, mode, profile, output, tolerance, token, ranges, sender)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn coordinate<S: Sender + 'static>(
+    listener: TcpListener,
+    blocks: &[Block],
+    mode: CrackerMode,
+    profile: BedrockProfile,
+    output: OutputMode,
+    tolerance: u32,
+    token: &str,
+    ranges: VecDeque<Range<u64>>,
+    sender: S,
+) -> io::Result<CrackHandle> {
+    listener.set_nonblocking(true)?;
+
+    let BedrockProfile { floor_y, roof_y, depth, floor_hash, roof_hash } = profile;
+    let mut setup = format!(
+        "SETUP {:?} {:?} {} {} {} {} {} {} {}\n",
+        mode, output, tolerance, floor_y, roof_y, depth, floor_hash, roof_hash, blocks.len()
+    );
+    for block in blocks {
+        setup.push_str(&format!("{}\n", block));
+    }
+
+    let queue = Arc::new(Mutex::new(WorkQueue {
+        pending: ranges,
+        ..Default::default()
+    }));
+
+    let token = token.to_string();
+    let handler_sender = sender.clone();
+    let accept = move |state: &SearchState| {
+        while !queue.lock().unwrap().is_done() {
+            // pausing only stops accepting workers, connected workers keep searching
+            if state.should_stop() {
+                queue.lock().unwrap().stopped = true;
+                return;
+            }
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let queue = queue.clone();
+                    let sender = handler_sender.clone();
+                    let setup = setup.clone();
+                    let token = token.clone();
+                    thread::spawn(move || {
+                        // a failing worker already gave its range back
+                        let _ = handle_worker(stream, &setup, &token, &queue, &sender);
+                    });
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                Err(_) => {
+                    state.cancel();
+                    queue.lock().unwrap().stopped = true;
+                    return;
+                }
+            }
+        }
+        if queue.lock().unwrap().stopped {
+            state.cancel();
+        }
+    };
+    Ok(CrackHandle::spawn(vec![accept], sender))
+}
+
+fn handle_worker<S: Sender>(stream: TcpStream, setup: &str, token: &str, queue: &SharedQueue, sender: &S) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(WORKER_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    let hello = read_line(&mut reader)?;
+    let mut parts = hello.split_whitespace();
+    if parts.next() != Some("HELLO") {
+        return Err(protocol_error(&hello));
+    }
+    if parts.nth(1).unwrap_or_default() != token {
+        return Err(io::Error::new(io::ErrorKind::PermissionDenied, "worker sent the wrong token"));
+    }
+    writer.write_all(setup.as_bytes())?;
+
+    while let Some(range) = next_range(queue) {
+        match search_range(&mut reader, &mut writer, &range, queue, sender) {
+            Ok(()) => {
+                let mut queue = queue.lock().unwrap();
+                queue.in_progress -= 1;
+                queue.reported.remove(&range.start);
+                if queue.stopped {
+                    break;
+                }
+                //dropping the receiver stops the search
+                if !sender.send(CrackProgress::Progress(range.end - range.start)) {
+                    queue.stopped = true;
+                }
+            }
+            Err(err) => {
+                let mut queue = queue.lock().unwrap();
+                queue.in_progress -= 1;
+                queue.pending.push_back(range);
+                return Err(err);
+            }
+        }
+    }
+    writeln!(writer, "FINISHED")
+}
+
+/// lets the worker search a range and reports the seeds that werent reported yet
+fn search_range<S: Sender>(reader: &mut impl BufRead, writer: &mut impl Write, range: &Range<u64>, queue: &SharedQueue, sender: &S) -> io::Result<()> {
+    writeln!(writer, "RANGE {} {}", range.start, range.end)?;
+
+    loop {
+        let line = read_line(reader)?;
+        let mut parts = line.split_whitespace();
+        let command = parts.next().unwrap_or_default();
+        let mut number = || parts.next().and_then(|num| num.parse::<u64>().ok()).ok_or_else(|| protocol_error(&line));
+        let result = match command {
+            "SEED" => (number()?, 0),
+            "PARTIAL" => {
+                let seed = number()?;
+                (seed, number()? as u32)
+            }
+            // progress only shows that the worker is alive, the range counts once it is done
+            "PROGRESS" | "ALIVE" => continue,
+            "DONE" => return Ok(()),
+            _ => return Err(protocol_error(&line)),
+        };
+        let mut queue = queue.lock().unwrap();
+        if queue.reported.entry(range.start).or_default().insert(result) && !sender.send(CrackProgress::result(result.0, result.1)) {
+            queue.stopped = true;
+        }
+    }
+}
+
+/// Connects to a coordinator and searches the ranges it hands out until everything is searched.
+/// token has to match the token of the coordinator
+pub fn run_worker<A: ToSocketAddrs>(address: A, thread_count: u64, token: &str) -> io::Result<()> {
+    let stream = TcpStream::connect(address)?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+
+    writeln!(writer, "HELLO {} {}", thread_count, token)?;
+
+    let setup = read_line(&mut reader)?;
+    let (mode, profile, output, tolerance, block_count) = parse_setup(&setup).ok_or_else(|| protocol_error(&setup))?;
+    let mut blocks = Vec::new();
+    for _ in 0..block_count {
+        let line = read_line(&mut reader)?;
+        // the same parser as block lists, impossible blocks are part of the crack and only use up tolerance
+        let block = match parse_line(&line, Dimension::Nether, profile) {
+            Ok(block) | Err(ConfigError::Impossible(block)) => block,
+            Err(_) => return Err(protocol_error(&line)),
+        };
+        blocks.push(block);
+    }
+
+    loop {
+        let line = read_line(&mut reader)?;
+        let mut parts = line.split_whitespace();
+        match parts.next() {
+            Some("RANGE") => {
+                let mut number = || parts.next().and_then(|num| num.parse::<u64>().ok()).ok_or_else(|| protocol_error(&line));
+                let range = number()?..number()?;
+
+                let (sender, receiver) = mpsc::channel();
+                search_bedrock_range(&blocks, thread_count, mode, profile, output, tolerance, range, sender);
+                // returning early drops the receiver which stops the search
+                loop {
+                    match receiver.recv_timeout(HEARTBEAT_INTERVAL) {
+                        Ok(CrackProgress::Seed(seed)) => writeln!(writer, "SEED {}", seed)?,
+                        Ok(CrackProgress::PartialMatch(seed, mismatches)) => writeln!(writer, "PARTIAL {} {}", seed, mismatches)?,
+                        Ok(CrackProgress::Progress(num)) => writeln!(writer, "PROGRESS {}", num)?,
+                        Ok(CrackProgress::ChunkDone(_) | CrackProgress::Finished | CrackProgress::Cancelled) => {}
+                        // weak filters can take longer than WORKER_TIMEOUT for one part of the range
+                        Err(RecvTimeoutError::Timeout) => writeln!(writer, "ALIVE")?,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                writeln!(writer, "DONE")?;
+            }
+            Some("FINISHED") => return Ok(()),
+            _ => return Err(protocol_error(&line)),
+        }
+    }
+}
+
+fn parse_setup(line: &str) -> Option<(CrackerMode, BedrockProfile, OutputMode, u32, usize)> {
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "SETUP" {
+        return None;
+    }
+    let mode = parts.next()?.parse().ok()?;
+    let output = parts.next()?.parse().ok()?;
+    let tolerance = parts.next()?.parse().ok()?;
+    let profile = BedrockProfile {
+        floor_y: parts.next()?.parse().ok()?,
+        roof_y: parts.next()?.parse().ok()?,
+        depth: parts.next()?.parse().ok()?,
+        floor_hash: parts.next()?.parse().ok()?,
+        roof_hash: parts.next()?.parse().ok()?,
+    };
+    let block_count = parts.next()?.parse().ok()?;
+    Some((mode, profile, output, tolerance, block_count))
+}
+
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(line.trim_end().to_string())
+}
+
+fn protocol_error(line: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("unexpected message: {line}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::handle::CrackOutcome;
+    use crate::layer::surface_seed;
+    use crate::{MASK48, ROOF_HASH};
+    use crate::benchmark::benchmark_blocks;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    #[test]
+    fn test_reassign_dead_worker() {
+        let blocks = benchmark_blocks();
+        let upper_bits = surface_seed(WORLD_SEED, ROOF_HASH) & MASK48 & !0xFFF;
+        let ranges = VecDeque::from([upper_bits..upper_bits + (1 << 12), 0..1 << 12]);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (sender, receiver) = mpsc::channel();
+        let handle = coordinate(listener, &blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, "secret", ranges, sender).unwrap();
+
+        // without the token nothing it sends is accepted
+        {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writeln!(writer, "HELLO 1 guess").unwrap();
+            let _ = writeln!(writer, "SEED 1");
+            assert!(read_line(&mut reader).is_err());
+        }
+
+        // takes a range, finds the seed and disconnects without finishing it
+        {
+            let stream = TcpStream::connect(address).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            writeln!(writer, "HELLO 1 secret").unwrap();
+            while !read_line(&mut reader).unwrap().starts_with("RANGE") {}
+            writeln!(writer, "SEED {}", WORLD_SEED).unwrap();
+            writeln!(writer, "ALIVE").unwrap();
+        }
+
+        run_worker(address, 2, "secret").unwrap();
+
+        let mut progress = 0;
+        let mut seeds = vec![];
+        let mut finished = false;
+        for event in receiver {
+            match event {
+                CrackProgress::Seed(seed) => seeds.push(seed),
+                CrackProgress::Progress(num) => progress += num,
+                CrackProgress::PartialMatch(_, _) => panic!("No tolerance was given"),
+                CrackProgress::ChunkDone(_) => panic!("No checkpoint was given"),
+                CrackProgress::Finished => finished = true,
+                CrackProgress::Cancelled => panic!("Nothing cancelled the search"),
+            }
+        }
+        // the seed of the dead worker is kept and not reported again
+        assert_eq!(seeds, vec![WORLD_SEED]);
+        assert_eq!(progress, 2 << 12);
+        assert!(finished);
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+    }
+
+    #[test]
+    fn test_broken_setup() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let coordinator = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // a block count that cant be allocated, the worker must not reserve it up front
+            writeln!(stream, "SETUP Normal WorldSeed 0 0 127 5 0 0 {}", usize::MAX).unwrap();
+            writeln!(stream, "1 2 3").unwrap();
+        });
+        assert!(run_worker(address, 1, "").is_err());
+        coordinator.join().unwrap();
+    }
+}
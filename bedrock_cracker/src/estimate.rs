@@ -0,0 +1,310 @@
+//! Expected amount of results and runtime of a nether crack
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::{MASK48, SEARCH_SPACE};
+use crate::benchmark::{benchmark_blocks, Calibration};
+use crate::block_data::BlockFilter;
+use crate::layer::{is_floor_primary, split_floor_roof};
+use crate::raw_data::block::Block;
+use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+
+/// seeds one thread searches per second on a desktop cpu, a Calibration knows the real speed
+pub const DEFAULT_SEEDS_PER_SECOND: f64 = 1.0e11;
+
+/// share of 48 bit values that next_long produces never or twice, all others have one preimage.
+/// on average every reverse_next_long step keeps the amount of seeds
+const NO_PREIMAGE: f64 = 0.1135;
+const TWO_PREIMAGES: f64 = 0.1135;
+
+/// a seed that passes the primary filter costs about as much as this many groups of 8 checks.
+/// benchmark::measure_cross_comparison_cost finds 13 to 15 in release builds, its test fails if they drift apart
+pub(crate) const CROSS_COMPARISON_COST: f64 = 14.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// expected amount of reported seeds, including the real one
+    pub results: f64,
+    pub seconds: f64,
+}
+
+/// The part of an Estimate that only depends on the blocks and settings, cheap to turn into an Estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchEstimate {
+    /// expected amount of reported seeds, including the real one
+    pub results: f64,
+    /// work of the search compared to the benchmark blocks
+    pub relative_cost: f64,
+}
+
+impl SearchEstimate {
+    pub fn new(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32) -> Self {
+        SearchEstimate {
+            results: expected_results(blocks, mode, profile, output, tolerance),
+            // the calibration measured the speed of the benchmark blocks, other blocks prune more or less of the tree
+            relative_cost: search_cost(blocks, mode, profile, tolerance) / benchmark_cost(),
+        }
+    }
+
+    /// the runtime of the whole search space on thread_count threads
+    pub fn with_speed(&self, thread_count: u64, calibration: &Calibration) -> Estimate {
+        Estimate {
+            results: self.results,
+            seconds: SEARCH_SPACE.end as f64 / calibration.seeds_per_second(thread_count) * self.relative_cost,
+        }
+    }
+}
+
+/// Estimates a crack of the whole search space
+pub fn estimate_crack(
+    blocks: &[Block],
+    mode: CrackerMode,
+    profile: BedrockProfile,
+    output: OutputMode,
+    tolerance: u32,
+    thread_count: u64,
+    calibration: &Calibration,
+) -> Estimate {
+    SearchEstimate::new(blocks, mode, profile, output, tolerance).with_speed(thread_count, calibration)
+}
+
+/// search_cost of the benchmark blocks, the same for every crack
+fn benchmark_cost() -> f64 {
+    static COST: OnceLock<f64> = OnceLock::new();
+    *COST.get_or_init(|| search_cost(&benchmark_blocks(), CrackerMode::Normal, BedrockProfile::NETHER, 0))
+}
+
+pub(crate) fn expected_results(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32) -> f64 {
+    let (floor, roof, impossible) = split_floor_roof(blocks, mode, profile);
+    // not even the real seed survives
+    let Some(allowed) = tolerance.checked_sub(impossible) else {
+        return 0.0;
+    };
+    let allowed = allowed as usize;
+
+    let (primary, secondary) = if is_floor_primary(&floor, &roof) {
+        (floor, roof)
+    } else {
+        (roof, floor)
+    };
+    let primary = mismatch_distribution(&primary, allowed);
+    let secondary = mismatch_distribution(&secondary, allowed);
+    let secondary_passes = |max_mismatches: usize| secondary[..=max_mismatches].iter().sum::<f64>();
+
+    // surface seeds of the primary filter that survive the whole tree
+    let surviving: f64 = primary
+        .iter()
+        .enumerate()
+        .map(|(mismatches, chance)| {
+            let passes = secondary_passes(allowed - mismatches);
+            chance * match output {
+                // at least one preimage has to pass the other surface
+                OutputMode::BedrockSeed => {
+                    let one_preimage = 1.0 - NO_PREIMAGE - TWO_PREIMAGES;
+                    1.0 - NO_PREIMAGE - one_preimage * (1.0 - passes) - TWO_PREIMAGES * (1.0 - passes).powi(2)
+                }
+                // every step back to the world seed yields one seed on average
+                OutputMode::StructureSeed | OutputMode::WorldSeed => passes,
+            }
+        })
+        .sum();
+
+    1.0 + surviving * SEARCH_SPACE.end as f64
+}
+
+/// expected work of one run_checks call of create_filter_tree, in groups of 8 checks
+pub(crate) fn search_cost(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, tolerance: u32) -> f64 {
+    let (checks, compared) = search_work(blocks, mode, profile, tolerance);
+    checks + compared * CROSS_COMPARISON_COST
+}
+
+/// groups of 8 checks and seeds that reach the comparison of the surfaces in one run_checks call
+pub(crate) fn search_work(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, tolerance: u32) -> (f64, f64) {
+    let (floor, roof, impossible) = split_floor_roof(blocks, mode, profile);
+    let primary = if is_floor_primary(&floor, &roof) { floor } else { roof };
+    // the first layer rejects everything
+    let Some(allowed) = tolerance.checked_sub(impossible) else {
+        return (1.0, 0.0);
+    };
+    let allowed = allowed as usize;
+    // a seed passes a layer if at most allowed blocks fail with the unknown bits of that layer
+    let passes = |bits: u64| {
+        let fail_chances: Vec<f64> = primary.iter().map(|block| 1.0 - block.pass_chance(bits)).collect();
+        at_most(&fail_chances, allowed)
+    };
+
+    let mut checks = 0.0;
+    // chance that a node of the current layer is visited, the first layer runs once per call
+    let mut reached = 1.0;
+    for bits in (0..=12).rev() {
+        let nodes = (1u64 << (12 - bits)) as f64 * reached;
+        checks += nodes * node_cost(&primary, bits, allowed);
+        reached = passes(bits);
+    }
+    (checks, (1u64 << 12) as f64 * reached)
+}
+
+/// groups of 8 checks that a visited node of a layer runs, without tolerance it stops at the first failing group
+fn node_cost(primary: &[BlockFilter], bits: u64, allowed: usize) -> f64 {
+    // chance to pass this layer after passing the previous one, sorted like the checks of create_filter_tree
+    let mut passes: Vec<f64> = primary
+        .iter()
+        .map(|block| {
+            // the first layer knows nothing about the seed yet
+            let previous = if bits == 12 { 1.0 } else { block.pass_chance(bits + 1) };
+            block.pass_chance(bits) / previous
+        })
+        .filter(|pass| *pass < 1.0)
+        .collect();
+    passes.sort_by(|a, b| b.partial_cmp(a).unwrap());
+    if allowed > 0 {
+        return passes.len().div_ceil(8).max(1) as f64;
+    }
+    let mut reached = 1.0;
+    passes
+        .chunks(8)
+        .map(|group| {
+            let cost = reached;
+            reached *= group.iter().product::<f64>();
+            cost
+        })
+        .sum::<f64>()
+        .max(1.0)
+}
+
+/// chance that at most max of the independent events happen
+fn at_most(chances: &[f64], max: usize) -> f64 {
+    let mut distribution = vec![0.0; max + 1];
+    distribution[0] = 1.0;
+    for chance in chances {
+        for happened in (0..=max).rev() {
+            let previous = if happened > 0 { distribution[happened - 1] } else { 0.0 };
+            distribution[happened] = distribution[happened] * (1.0 - chance) + previous * chance;
+        }
+    }
+    distribution.iter().sum()
+}
+
+/// chance that a random surface seed contradicts exactly 0, 1, ... max of the blocks
+fn mismatch_distribution(filters: &[BlockFilter], max: usize) -> Vec<f64> {
+    // blocks with the same position hash share their random, e.g. columns in paper mode
+    let mut groups: HashMap<u64, Vec<(u64, u64)>> = HashMap::new();
+    for filter in filters {
+        groups.entry(filter.pos_hash()).or_default().push(filter.pass_range());
+    }
+
+    let mut distribution = vec![0.0; max + 1];
+    distribution[0] = 1.0;
+    for ranges in groups.values() {
+        let group = group_distribution(ranges);
+        let mut next = vec![0.0; max + 1];
+        for (a, chance_a) in distribution.iter().enumerate() {
+            for (b, chance_b) in group.iter().enumerate().take(max + 1 - a) {
+                next[a + b] += chance_a * chance_b;
+            }
+        }
+        distribution = next;
+    }
+    distribution
+}
+
+/// the random of a group is uniform, every interval between two bounds has a fixed amount of mismatches
+fn group_distribution(ranges: &[(u64, u64)]) -> Vec<f64> {
+    let mut points: Vec<u64> = ranges
+        .iter()
+        .flat_map(|&(lower, upper)| [lower, upper])
+        .chain([0, MASK48])
+        .filter(|point| *point <= MASK48)
+        .collect();
+    points.sort_unstable();
+    points.dedup();
+
+    let mut distribution = vec![0.0; ranges.len() + 1];
+    for segment in points.windows(2) {
+        let middle = segment[0] + (segment[1] - segment[0]) / 2;
+        let mismatches = ranges
+            .iter()
+            .filter(|(lower, upper)| !(*lower..*upper).contains(&middle))
+            .count();
+        distribution[mismatches] += (segment[1] - segment[0]) as f64 / MASK48 as f64;
+    }
+    distribution
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raw_data::block_type::BlockType;
+    use super::*;
+
+    const SPACE: f64 = SEARCH_SPACE.end as f64;
+
+    fn results(blocks: &[Block], mode: CrackerMode, output: OutputMode, tolerance: u32) -> f64 {
+        estimate_crack(blocks, mode, BedrockProfile::NETHER, output, tolerance, 1, &Calibration::default()).results
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a / b - 1.0).abs() < 1e-3, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_output_modes() {
+        assert_close(results(&[], CrackerMode::Normal, OutputMode::WorldSeed, 0), SPACE);
+        assert_close(results(&[], CrackerMode::Normal, OutputMode::StructureSeed, 0), SPACE);
+        assert_close(results(&[], CrackerMode::Normal, OutputMode::BedrockSeed, 0), SPACE * (1.0 - NO_PREIMAGE));
+
+        let blocks = [Block::new(0, 4, 0, BlockType::BEDROCK), Block::new(0, 123, 0, BlockType::BEDROCK)];
+        assert_close(results(&blocks, CrackerMode::Normal, OutputMode::WorldSeed, 0), SPACE * 0.04);
+    }
+
+    #[test]
+    fn test_paper_columns() {
+        // 20% and 40% chance, but paper uses the same random for the whole column
+        let blocks = [Block::new(0, 4, 0, BlockType::BEDROCK), Block::new(0, 3, 0, BlockType::BEDROCK)];
+        assert_close(results(&blocks, CrackerMode::Normal, OutputMode::StructureSeed, 0), SPACE * 0.08);
+        assert_close(results(&blocks, CrackerMode::Paper1_18, OutputMode::StructureSeed, 0), SPACE * 0.2);
+    }
+
+    #[test]
+    fn test_tolerance() {
+        let blocks = [Block::new(0, 4, 0, BlockType::BEDROCK), Block::new(1, 4, 0, BlockType::BEDROCK)];
+        assert_close(results(&blocks, CrackerMode::Normal, OutputMode::StructureSeed, 1), SPACE * (0.04 + 2.0 * 0.2 * 0.8));
+        assert_close(results(&blocks, CrackerMode::Normal, OutputMode::StructureSeed, 2), SPACE);
+
+        // other on y=0 is impossible and uses up the tolerance
+        let impossible = [Block::new(0, 0, 0, BlockType::OTHER)];
+        assert_eq!(results(&impossible, CrackerMode::Normal, OutputMode::WorldSeed, 0), 0.0);
+        assert_close(results(&impossible, CrackerMode::Normal, OutputMode::WorldSeed, 1), SPACE);
+    }
+
+    #[test]
+    fn test_runtime() {
+        let calibration = Calibration { measurements: vec![(1, 1.0e11), (4, 2.0e11)] };
+        let blocks = benchmark_blocks();
+        let one = estimate_crack(&blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, 1, &calibration);
+        let four = estimate_crack(&blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, 4, &calibration);
+        // the calibration measured the benchmark blocks
+        assert_close(one.seconds, SPACE / 1.0e11);
+        // the measurement of 4 threads wins over scaling the single thread
+        assert_close(one.seconds, four.seconds * 2.0);
+    }
+
+    #[test]
+    fn test_runtime_of_blocks() {
+        let calibration = Calibration { measurements: vec![(1, 1.0e11)] };
+        let seconds = |blocks: &[Block]| {
+            estimate_crack(blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, 1, &calibration).seconds
+        };
+        let blocks = |count: i32, block_type: BlockType| -> Vec<Block> {
+            (0..count).map(|x| Block::new(x, 4, 0, block_type)).collect()
+        };
+        let benchmark = seconds(&benchmark_blocks());
+
+        // common blocks let most seeds through to the expensive comparison of the surfaces
+        assert!(seconds(&blocks(20, BlockType::OTHER)) > 100.0 * benchmark);
+        assert!(seconds(&blocks(10, BlockType::OTHER)) > seconds(&blocks(20, BlockType::OTHER)));
+        assert!(seconds(&[]) > seconds(&blocks(10, BlockType::OTHER)));
+        // rare blocks prune the tree right at the top
+        assert!(seconds(&blocks(20, BlockType::BEDROCK)) < benchmark);
+    }
+}
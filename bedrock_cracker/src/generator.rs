@@ -0,0 +1,130 @@
+use java_random::Random;
+use crate::block_data::BlockFilter;
+use crate::layer::surface_seed;
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::modes::CrackerMode;
+use crate::raw_data::profile::BedrockProfile;
+
+/// Places nether bedrock the same way the game does for a known seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BedrockGenerator {
+    floor_seed: u64,
+    roof_seed: u64,
+    mode: CrackerMode,
+    profile: BedrockProfile,
+}
+
+impl BedrockGenerator {
+    /// Accepts world seeds and structure seeds, bedrock only depends on the lower 48 bits
+    pub fn new(seed: u64, mode: CrackerMode) -> Self {
+        Self::with_profile(seed, mode, BedrockProfile::NETHER)
+    }
+
+    pub fn with_profile(seed: u64, mode: CrackerMode, profile: BedrockProfile) -> Self {
+        Self {
+            floor_seed: surface_seed(seed, profile.floor_hash),
+            roof_seed: surface_seed(seed, profile.roof_hash),
+            mode,
+            profile,
+        }
+    }
+
+    /// For seeds of OutputMode::BedrockSeed, only the surface the seeds belong to is right
+    pub fn from_surface_seeds(floor_seed: u64, roof_seed: u64, mode: CrackerMode, profile: BedrockProfile) -> Self {
+        Self {
+            floor_seed,
+            roof_seed,
+            mode,
+            profile,
+        }
+    }
+
+    /// None if y is not part of the floor or roof gradient
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
+        let (bedrock_seed, is_floor) = if self.profile.floor_layers().contains(&y) {
+            (self.floor_seed, true)
+        } else if self.profile.roof_layers().contains(&y) {
+            (self.roof_seed, false)
+        } else {
+            return None;
+        };
+
+        let pos_hash = BlockFilter::hashcode(x, BlockFilter::random_y(y, self.mode, self.profile), z);
+        let roll = Random::with_seed(pos_hash ^ bedrock_seed).next_float() as f64;
+        let passes_gradient = roll < self.profile.gradient(y);
+
+        Some(if passes_gradient == is_floor {
+            BlockType::BEDROCK
+        } else {
+            BlockType::OTHER
+        })
+    }
+
+    /// All floor and roof blocks in the 16x16 column of a chunk
+    pub fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Vec<Block> {
+        let (x, z) = (chunk_x * 16, chunk_z * 16);
+        self.generate_area(x, z, x + 15, z + 15)
+    }
+
+    /// All floor and roof blocks between the two corners (inclusive)
+    pub fn generate_area(&self, x1: i32, z1: i32, x2: i32, z2: i32) -> Vec<Block> {
+        let mut blocks = vec![];
+        for y in self.profile.floor_layers().chain(self.profile.roof_layers()) {
+            for z in z1.min(z2)..=z1.max(z2) {
+                for x in x1.min(x2)..=x1.max(x2) {
+                    if let Some(block_type) = self.block_at(x, y, z) {
+                        blocks.push(Block::new(x, y, z, block_type));
+                    }
+                }
+            }
+        }
+        blocks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::MASK48;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    #[test]
+    fn test_known_blocks() {
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let blocks = [
+            Block::new(18, 123, -117, BlockType::OTHER),
+            Block::new(33, 126, -99, BlockType::OTHER),
+            Block::new(19, 123, -117, BlockType::BEDROCK),
+            Block::new(25, 126, -112, BlockType::BEDROCK),
+            Block::new(11, 1, -111, BlockType::OTHER),
+            Block::new(14, 4, -97, BlockType::OTHER),
+            Block::new(10, 1, -111, BlockType::BEDROCK),
+            Block::new(11, 4, -97, BlockType::BEDROCK),
+        ];
+        for block in blocks {
+            assert_eq!(generator.block_at(block.x, block.y, block.z), Some(block.block_type), "{block}");
+        }
+    }
+
+    #[test]
+    fn test_fixed_layers() {
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let chunk = generator.generate_chunk(-2, 5);
+        assert_eq!(chunk.len(), 16 * 16 * 10);
+        assert!(chunk
+            .iter()
+            .filter(|block| block.y == 0 || block.y == 127)
+            .all(|block| block.block_type == BlockType::BEDROCK));
+        assert_eq!(generator.block_at(0, 64, 0), None);
+    }
+
+    #[test]
+    fn test_structure_seed() {
+        let structure_seed = WORLD_SEED & MASK48;
+        assert_eq!(
+            BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal),
+            BedrockGenerator::new(structure_seed, CrackerMode::Normal)
+        );
+    }
+}
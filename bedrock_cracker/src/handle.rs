@@ -0,0 +1,194 @@
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+use crate::CrackProgress;
+use crate::raw_data::sender::Sender;
+
+/// How a search ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrackOutcome {
+    Finished,
+    Cancelled,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SearchState {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+    pause_lock: Mutex<()>,
+    resumed: Condvar,
+    running: AtomicUsize,
+}
+
+impl SearchState {
+    pub(crate) fn cancel(&self) {
+        // the lock makes sure paused threads dont miss the wake up
+        let _lock = self.pause_lock.lock().unwrap();
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.resumed.notify_all();
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    fn resume(&self) {
+        let _lock = self.pause_lock.lock().unwrap();
+        self.paused.store(false, Ordering::Relaxed);
+        self.resumed.notify_all();
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Blocks while the search is paused, true if the thread has to stop
+    pub(crate) fn should_stop(&self) -> bool {
+        if self.is_paused() {
+            let mut lock = self.pause_lock.lock().unwrap();
+            while self.is_paused() && !self.is_cancelled() {
+                lock = self.resumed.wait(lock).unwrap();
+            }
+        }
+        self.is_cancelled()
+    }
+
+    fn outcome(&self) -> CrackOutcome {
+        if self.is_cancelled() {
+            CrackOutcome::Cancelled
+        } else {
+            CrackOutcome::Finished
+        }
+    }
+}
+
+/// Stops or pauses a running search, can be shared with other threads
+#[derive(Debug, Clone)]
+pub struct CancelToken {
+    state: Arc<SearchState>,
+}
+
+impl CancelToken {
+    /// Stops all threads within a few million seeds, the sender then gets CrackProgress::Cancelled
+    pub fn cancel(&self) {
+        self.state.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+
+    /// Suspends all threads where they currently are until resume or cancel is called
+    pub fn pause(&self) {
+        self.state.pause();
+    }
+
+    pub fn resume(&self) {
+        self.state.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.is_paused()
+    }
+}
+
+/// The threads of a running search.
+/// Dropping the handle doesnt stop the search, it keeps running in the background
+#[derive(Debug)]
+pub struct CrackHandle {
+    state: Arc<SearchState>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl CrackHandle {
+    /// Runs every job on its own thread. Jobs have to call SearchState::should_stop regularly.
+    /// After the last job returned the sender gets CrackProgress::Finished or CrackProgress::Cancelled
+    pub(crate) fn spawn<S, F>(jobs: Vec<F>, sender: S) -> CrackHandle
+    where
+        S: Sender + 'static,
+        F: FnOnce(&SearchState) + Send + 'static,
+    {
+        let state = Arc::new(SearchState {
+            running: AtomicUsize::new(jobs.len()),
+            ..Default::default()
+        });
+        if jobs.is_empty() {
+            sender.send(CrackProgress::Finished);
+        }
+
+        let threads = jobs
+            .into_iter()
+            .map(|job| {
+                let state = state.clone();
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    job(&state);
+                    // the last thread reports the end after every other event was sent
+                    if state.running.fetch_sub(1, Ordering::AcqRel) == 1 {
+                        sender.send(match state.outcome() {
+                            CrackOutcome::Finished => CrackProgress::Finished,
+                            CrackOutcome::Cancelled => CrackProgress::Cancelled,
+                        });
+                    }
+                })
+            })
+            .collect();
+
+        CrackHandle { state, threads }
+    }
+
+    /// A search that got cancelled before any thread was started
+    pub(crate) fn cancelled() -> CrackHandle {
+        let state = SearchState::default();
+        state.cancel();
+        CrackHandle {
+            state: Arc::new(state),
+            threads: vec![],
+        }
+    }
+
+    pub fn token(&self) -> CancelToken {
+        CancelToken {
+            state: self.state.clone(),
+        }
+    }
+
+    /// see CancelToken::cancel
+    pub fn cancel(&self) {
+        self.state.cancel();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.is_cancelled()
+    }
+
+    /// see CancelToken::pause
+    pub fn pause(&self) {
+        self.state.pause();
+    }
+
+    pub fn resume(&self) {
+        self.state.resume();
+    }
+
+    /// true once every thread stopped, either because the search is done or because it got cancelled
+    pub fn is_finished(&self) -> bool {
+        self.state.running.load(Ordering::Acquire) == 0
+    }
+
+    /// Waits for all threads to stop
+    pub fn join(self) -> CrackOutcome {
+        for thread in self.threads {
+            if let Err(err) = thread.join() {
+                panic::resume_unwind(err);
+            }
+        }
+        self.state.outcome()
+    }
+}
@@ -0,0 +1,281 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use flate2::read::{GzDecoder, ZlibDecoder};
+
+use crate::import::nbt::{self, invalid, Tag};
+use crate::import::imported_layers;
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::profile::BedrockProfile;
+
+const SECTOR: usize = 4096;
+
+/// chunks in these states dont have their bedrock yet
+const UNFINISHED: [&str; 5] = [
+    "minecraft:empty",
+    "minecraft:structure_starts",
+    "minecraft:structure_references",
+    "minecraft:biomes",
+    "minecraft:noise",
+];
+
+/// Reads the floor and roof layers of every generated chunk in a region file (r.x.z.mca)
+pub fn read_region<P: AsRef<Path>>(path: P, profile: BedrockProfile) -> io::Result<Vec<Block>> {
+    let region = fs::read(path)?;
+    let mut blocks = vec![];
+    for index in 0..1024 {
+        if let Some(chunk) = read_chunk(&region, index)? {
+            blocks.extend(chunk_blocks(&chunk, profile)?);
+        }
+    }
+    Ok(blocks)
+}
+
+/// Reads the floor and roof layers between x1 z1 and x2 z2 (inclusive) from a world folder.
+/// The folder can be the world itself, a world_nether folder of a server or the region folder.
+/// Chunks that arent generated are skipped
+pub fn read_nether<P: AsRef<Path>>(world: P, x1: i32, z1: i32, x2: i32, z2: i32, profile: BedrockProfile) -> io::Result<Vec<Block>> {
+    let dir = region_folder(world.as_ref());
+    let mut regions: HashMap<(i32, i32), Option<Vec<u8>>> = HashMap::new();
+    let mut blocks = vec![];
+
+    for chunk_x in x1 >> 4..=x2 >> 4 {
+        for chunk_z in z1 >> 4..=z2 >> 4 {
+            let region_pos = (chunk_x >> 5, chunk_z >> 5);
+            let region = match regions.entry(region_pos) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => entry.insert(load_region(&dir, region_pos)?),
+            };
+            let Some(region) = region else {
+                continue;
+            };
+
+            let index = (chunk_x & 31) as usize + (chunk_z & 31) as usize * 32;
+            if let Some(chunk) = read_chunk(region, index)? {
+                blocks.extend(
+                    chunk_blocks(&chunk, profile)?
+                        .into_iter()
+                        .filter(|block| (x1..=x2).contains(&block.x) && (z1..=z2).contains(&block.z)),
+                );
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+fn load_region(dir: &Path, (region_x, region_z): (i32, i32)) -> io::Result<Option<Vec<u8>>> {
+    match fs::read(dir.join(format!("r.{region_x}.{region_z}.mca"))) {
+        Ok(region) => Ok(Some(region)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn region_folder(world: &Path) -> PathBuf {
+    let nether = world.join("DIM-1").join("region");
+    if nether.is_dir() {
+        nether
+    } else {
+        world.to_path_buf()
+    }
+}
+
+/// None if the chunk was never generated
+fn read_chunk(region: &[u8], index: usize) -> io::Result<Option<Tag>> {
+    let location = region
+        .get(index * 4..index * 4 + 4)
+        .ok_or_else(|| invalid("region file is too short"))?;
+    let offset = u32::from_be_bytes([0, location[0], location[1], location[2]]) as usize * SECTOR;
+    if offset == 0 {
+        return Ok(None);
+    }
+
+    let header = region
+        .get(offset..offset + 5)
+        .ok_or_else(|| invalid("chunk outside of the region file"))?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+    let data = length
+        .checked_sub(1)
+        .and_then(|length| region.get(offset + 5..offset + 5 + length))
+        .ok_or_else(|| invalid("chunk outside of the region file"))?;
+
+    let mut reader: Box<dyn Read> = match header[4] {
+        1 => Box::new(GzDecoder::new(data)),
+        2 => Box::new(ZlibDecoder::new(data)),
+        3 => Box::new(data),
+        compression if compression & 0x80 != 0 => return Err(invalid("chunks in external .mcc files arent supported")),
+        _ => return Err(invalid("unsupported chunk compression")),
+    };
+    nbt::read(&mut reader).map(Some)
+}
+
+fn chunk_blocks(chunk: &Tag, profile: BedrockProfile) -> io::Result<Vec<Block>> {
+    // chunks from before 1.18 have a different format and bedrock pattern anyways
+    if chunk.get("Level").is_some() {
+        return Ok(vec![]);
+    }
+    let status = chunk.get("Status").and_then(Tag::as_str).unwrap_or_default();
+    if UNFINISHED.contains(&status) {
+        return Ok(vec![]);
+    }
+
+    let int = |name: &str| chunk.get(name).and_then(Tag::as_int).ok_or_else(|| invalid("chunk without position"));
+    let (chunk_x, chunk_z) = (int("xPos")? as i32, int("zPos")? as i32);
+    let sections = chunk.get("sections").and_then(Tag::as_list).unwrap_or_default();
+
+    let mut blocks = vec![];
+    for section in sections {
+        let Some(section_y) = section.get("Y").and_then(Tag::as_int) else {
+            continue;
+        };
+        let layers: Vec<_> = imported_layers(profile)
+            .filter(|y| (y >> 4) as i64 == section_y)
+            .collect();
+        if layers.is_empty() {
+            continue;
+        }
+        let states = section.get("block_states").ok_or_else(|| invalid("section without blocks"))?;
+        let is_bedrock = section_palette(states)?;
+        for y in layers {
+            for z in 0..16 {
+                for x in 0..16 {
+                    let index = (y.rem_euclid(16) * 256 + z * 16 + x) as usize;
+                    let block_type = match is_bedrock(index) {
+                        true => BlockType::BEDROCK,
+                        false => BlockType::OTHER,
+                    };
+                    blocks.push(Block::new(chunk_x * 16 + x, y, chunk_z * 16 + z, block_type));
+                }
+            }
+        }
+    }
+    Ok(blocks)
+}
+
+/// whether the block at a section index is bedrock
+fn section_palette(states: &Tag) -> io::Result<impl Fn(usize) -> bool + '_> {
+    let palette: Vec<bool> = states
+        .get("palette")
+        .and_then(Tag::as_list)
+        .ok_or_else(|| invalid("section without palette"))?
+        .iter()
+        .map(|entry| entry.get("Name").and_then(Tag::as_str) == Some("minecraft:bedrock"))
+        .collect();
+    let data = states.get("data").and_then(Tag::as_long_array).unwrap_or_default();
+
+    // entries dont span multiple longs
+    let bits = (usize::BITS - palette.len().saturating_sub(1).leading_zeros()).max(4) as usize;
+    let per_long = 64 / bits;
+    if palette.is_empty() || palette.len() > 1 && data.len() < 4096usize.div_ceil(per_long) {
+        return Err(invalid("section with broken block data"));
+    }
+
+    Ok(move |index: usize| {
+        let entry = match data {
+            [] => 0,
+            data => (data[index / per_long] as u64 >> (index % per_long * bits)) & ((1 << bits) - 1),
+        };
+        palette.get(entry as usize).copied().unwrap_or(false)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::Write;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use crate::generator::BedrockGenerator;
+    use crate::import::nbt::tests::{compound, write};
+    use crate::raw_data::modes::CrackerMode;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    fn block_name(name: &str) -> Tag {
+        compound([("Name", Tag::String(name.to_string()))])
+    }
+
+    /// a section with netherrack and bedrock like minecraft writes it
+    fn section(section_y: i32, generator: &BedrockGenerator, chunk_x: i32, chunk_z: i32) -> Tag {
+        let mut data = vec![0i64; 4096 / 16];
+        for index in 0..4096 {
+            let (y, z, x) = (index as i32 / 256, index as i32 / 16 % 16, index as i32 % 16);
+            let block = generator.block_at(chunk_x * 16 + x, section_y * 16 + y, chunk_z * 16 + z);
+            if block == Some(BlockType::BEDROCK) {
+                data[index / 16] |= 1 << (index % 16 * 4);
+            }
+        }
+        compound([
+            ("Y", Tag::Byte(section_y as i8)),
+            ("block_states", compound([
+                ("palette", Tag::List(vec![block_name("minecraft:netherrack"), block_name("minecraft:bedrock")])),
+                ("data", Tag::LongArray(data)),
+            ])),
+        ])
+    }
+
+    fn region_file(chunks: &[(i32, i32, Tag)]) -> Vec<u8> {
+        let mut region = vec![0; 2 * SECTOR];
+        for (chunk_x, chunk_z, chunk) in chunks {
+            let mut nbt = vec![];
+            write(chunk, &mut nbt);
+            let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+            encoder.write_all(&nbt).unwrap();
+            let compressed = encoder.finish().unwrap();
+
+            let index = (chunk_x & 31) as usize + (chunk_z & 31) as usize * 32;
+            let sector = region.len() / SECTOR;
+            let sectors = (compressed.len() + 5).div_ceil(SECTOR);
+            region[index * 4..index * 4 + 4].copy_from_slice(&((sector << 8 | sectors) as u32).to_be_bytes());
+
+            region.extend_from_slice(&(compressed.len() as u32 + 1).to_be_bytes());
+            region.push(2);
+            region.extend_from_slice(&compressed);
+            region.resize(region.len().div_ceil(SECTOR) * SECTOR, 0);
+        }
+        region
+    }
+
+    fn sorted(mut blocks: Vec<Block>) -> Vec<Block> {
+        blocks.sort_by_key(|block| (block.x, block.y, block.z));
+        blocks
+    }
+
+    #[test]
+    fn test_read_nether() {
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let chunk = |chunk_x: i32, chunk_z: i32, status: &str| {
+            (chunk_x, chunk_z, compound([
+                ("xPos", Tag::Int(chunk_x)),
+                ("zPos", Tag::Int(chunk_z)),
+                ("Status", Tag::String(status.to_string())),
+                ("sections", Tag::List(vec![
+                    section(0, &generator, chunk_x, chunk_z),
+                    section(7, &generator, chunk_x, chunk_z),
+                ])),
+            ]))
+        };
+        let region = region_file(&[chunk(-1, -1, "minecraft:full"), chunk(-2, -1, "minecraft:noise")]);
+
+        let world = env::temp_dir().join(format!("bedrock_world_{}", std::process::id()));
+        let dir = world.join("DIM-1").join("region");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("r.-1.-1.mca"), region).unwrap();
+
+        let profile = BedrockProfile::NETHER;
+        let blocks = read_nether(&world, -20, -5, 3, -1, profile).unwrap();
+        let expected = generator
+            .generate_area(-16, -5, -1, -1)
+            .into_iter()
+            .filter(|block| imported_layers(profile).any(|y| y == block.y))
+            .collect();
+        assert_eq!(sorted(blocks), sorted(expected));
+        assert_eq!(read_region(dir.join("r.-1.-1.mca"), profile).unwrap().len(), 16 * 16 * 8);
+
+        fs::remove_dir_all(&world).unwrap();
+    }
+}
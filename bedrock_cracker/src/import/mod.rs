@@ -0,0 +1,15 @@
+//! Reads bedrock from minecraft files instead of typing the coordinates by hand
+
+use crate::raw_data::profile::BedrockProfile;
+
+pub mod anvil;
+mod nbt;
+pub mod schematic;
+
+/// the layers that get imported, the solid layers are always bedrock so they dont help the cracker
+pub fn imported_layers(profile: BedrockProfile) -> impl Iterator<Item = i32> {
+    profile
+        .floor_layers()
+        .chain(profile.roof_layers())
+        .filter(move |y| !profile.is_solid(*y))
+}
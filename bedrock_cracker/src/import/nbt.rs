@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+/// A decoded NBT tag, only as much as the importers need
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    List(Vec<Tag>),
+    Compound(HashMap<String, Tag>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    pub(crate) fn get(&self, name: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(tags) => tags.get(name),
+            _ => None,
+        }
+    }
+
+    /// any integer tag, schematics dont agree on the integer sizes
+    pub(crate) fn as_int(&self) -> Option<i64> {
+        match *self {
+            Tag::Byte(value) => Some(value as i64),
+            Tag::Short(value) => Some(value as i64),
+            Tag::Int(value) => Some(value as i64),
+            Tag::Long(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(tags) => Some(tags),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_compound(&self) -> Option<&HashMap<String, Tag>> {
+        match self {
+            Tag::Compound(tags) => Some(tags),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_byte_array(&self) -> Option<&[i8]> {
+        match self {
+            Tag::ByteArray(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Tag::LongArray(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Reads an uncompressed NBT file, the name of the root tag is dropped
+pub(crate) fn read(reader: &mut impl Read) -> io::Result<Tag> {
+    let id = read_u8(reader)?;
+    if id != 10 {
+        return Err(invalid("the root tag isnt a compound"));
+    }
+    read_string(reader)?;
+    read_payload(reader, id, 0)
+}
+
+fn read_payload(reader: &mut impl Read, id: u8, depth: u32) -> io::Result<Tag> {
+    // protects against stack overflows from broken files
+    if depth > 512 {
+        return Err(invalid("tags are nested too deep"));
+    }
+    Ok(match id {
+        1 => Tag::Byte(read_u8(reader)? as i8),
+        2 => Tag::Short(i16::from_be_bytes(read_bytes(reader)?)),
+        3 => Tag::Int(read_i32(reader)?),
+        4 => Tag::Long(i64::from_be_bytes(read_bytes(reader)?)),
+        5 => Tag::Float(f32::from_be_bytes(read_bytes(reader)?)),
+        6 => Tag::Double(f64::from_be_bytes(read_bytes(reader)?)),
+        7 => {
+            let len = read_len(reader)?;
+            let mut bytes = vec![0; len];
+            reader.read_exact(&mut bytes)?;
+            Tag::ByteArray(bytes.into_iter().map(|byte| byte as i8).collect())
+        }
+        8 => Tag::String(read_string(reader)?),
+        9 => {
+            let id = read_u8(reader)?;
+            let len = read_len(reader)?;
+            let mut tags = Vec::with_capacity(len.min(1024));
+            for _ in 0..len {
+                tags.push(read_payload(reader, id, depth + 1)?);
+            }
+            Tag::List(tags)
+        }
+        10 => {
+            let mut tags = HashMap::new();
+            loop {
+                let id = read_u8(reader)?;
+                if id == 0 {
+                    break;
+                }
+                let name = read_string(reader)?;
+                tags.insert(name, read_payload(reader, id, depth + 1)?);
+            }
+            Tag::Compound(tags)
+        }
+        11 => {
+            let len = read_len(reader)?;
+            let mut values = Vec::with_capacity(len.min(1 << 16));
+            for _ in 0..len {
+                values.push(read_i32(reader)?);
+            }
+            Tag::IntArray(values)
+        }
+        12 => {
+            let len = read_len(reader)?;
+            let mut values = Vec::with_capacity(len.min(1 << 16));
+            for _ in 0..len {
+                values.push(i64::from_be_bytes(read_bytes(reader)?));
+            }
+            Tag::LongArray(values)
+        }
+        _ => return Err(invalid("unknown tag type")),
+    })
+}
+
+fn read_bytes<const N: usize>(reader: &mut impl Read) -> io::Result<[u8; N]> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    Ok(read_bytes::<1>(reader)?[0])
+}
+
+fn read_i32(reader: &mut impl Read) -> io::Result<i32> {
+    Ok(i32::from_be_bytes(read_bytes(reader)?))
+}
+
+fn read_len(reader: &mut impl Read) -> io::Result<usize> {
+    usize::try_from(read_i32(reader)?).map_err(|_| invalid("negative length"))
+}
+
+fn read_string(reader: &mut impl Read) -> io::Result<String> {
+    let len = u16::from_be_bytes(read_bytes(reader)?);
+    let mut bytes = vec![0; len as usize];
+    reader.read_exact(&mut bytes)?;
+    // java uses modified utf8, block names are plain ascii anyways
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+pub(crate) fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Writes the tag like minecraft would, the importer tests build their files with it
+    pub(crate) fn write(tag: &Tag, out: &mut Vec<u8>) {
+        out.push(10);
+        out.extend_from_slice(&0u16.to_be_bytes());
+        write_payload(tag, out);
+    }
+
+    fn id(tag: &Tag) -> u8 {
+        match tag {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    fn write_string(value: &str, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        out.extend_from_slice(value.as_bytes());
+    }
+
+    fn write_payload(tag: &Tag, out: &mut Vec<u8>) {
+        match tag {
+            Tag::Byte(value) => out.push(*value as u8),
+            Tag::Short(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Tag::Int(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Tag::Long(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Tag::Float(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Tag::Double(value) => out.extend_from_slice(&value.to_be_bytes()),
+            Tag::ByteArray(values) => {
+                out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+                out.extend(values.iter().map(|value| *value as u8));
+            }
+            Tag::String(value) => write_string(value, out),
+            Tag::List(tags) => {
+                out.push(tags.first().map_or(0, id));
+                out.extend_from_slice(&(tags.len() as i32).to_be_bytes());
+                for tag in tags {
+                    write_payload(tag, out);
+                }
+            }
+            Tag::Compound(tags) => {
+                for (name, tag) in tags {
+                    out.push(id(tag));
+                    write_string(name, out);
+                    write_payload(tag, out);
+                }
+                out.push(0);
+            }
+            Tag::IntArray(values) => {
+                out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+                for value in values {
+                    out.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+            Tag::LongArray(values) => {
+                out.extend_from_slice(&(values.len() as i32).to_be_bytes());
+                for value in values {
+                    out.extend_from_slice(&value.to_be_bytes());
+                }
+            }
+        }
+    }
+
+    pub(crate) fn compound<const N: usize>(tags: [(&str, Tag); N]) -> Tag {
+        Tag::Compound(tags.into_iter().map(|(name, tag)| (name.to_string(), tag)).collect())
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let tag = compound([
+            ("Name", Tag::String("minecraft:bedrock".to_string())),
+            ("data", Tag::LongArray(vec![-1, 0, 1 << 40])),
+            ("Y", Tag::Byte(-4)),
+            ("list", Tag::List(vec![compound([("x", Tag::Int(5))])])),
+            ("bytes", Tag::ByteArray(vec![-1, 2])),
+        ]);
+        let mut bytes = vec![];
+        write(&tag, &mut bytes);
+        assert_eq!(read(&mut bytes.as_slice()).unwrap(), tag);
+        assert!(read(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,290 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+use crate::import::nbt::{self, invalid, Tag};
+use crate::import::imported_layers;
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::profile::BedrockProfile;
+
+/// What a schematic stores at a position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Bedrock,
+    Other,
+    /// positions the schematic didnt capture
+    Unknown,
+}
+
+impl Cell {
+    fn from_name(name: &str) -> Cell {
+        // block states look like minecraft:stone_slab[type=top]
+        match name.split('[').next().unwrap_or_default() {
+            "minecraft:bedrock" => Cell::Bedrock,
+            "minecraft:structure_void" => Cell::Unknown,
+            _ => Cell::Other,
+        }
+    }
+}
+
+/// Reads the floor and roof layers of a Litematica (.litematic) or Sponge (.schem) schematic.
+/// x y z is the world position of the schematic origin, the position the schematic was saved at.
+/// For Sponge schematics that is the lowest corner, Litematica regions are relative to the origin
+pub fn read_schematic<P: AsRef<Path>>(path: P, x: i32, y: i32, z: i32, profile: BedrockProfile) -> io::Result<Vec<Block>> {
+    let file = File::open(path)?;
+    let schematic = nbt::read(&mut GzDecoder::new(BufReader::new(file)))?;
+    parse_schematic(&schematic, x, y, z, profile)
+}
+
+fn parse_schematic(schematic: &Tag, x: i32, y: i32, z: i32, profile: BedrockProfile) -> io::Result<Vec<Block>> {
+    let layers: Vec<_> = imported_layers(profile).collect();
+    let mut blocks = vec![];
+    let mut push = |pos_x: i32, pos_y: i32, pos_z: i32, cell: Cell| {
+        let (pos_x, pos_y, pos_z) = (x + pos_x, y + pos_y, z + pos_z);
+        if !layers.contains(&pos_y) {
+            return;
+        }
+        match cell {
+            Cell::Bedrock => blocks.push(Block::new(pos_x, pos_y, pos_z, BlockType::BEDROCK)),
+            Cell::Other => blocks.push(Block::new(pos_x, pos_y, pos_z, BlockType::OTHER)),
+            Cell::Unknown => {}
+        }
+    };
+
+    if let Some(regions) = schematic.get("Regions") {
+        read_litematic(regions, &mut push)?;
+    } else if let Some(sponge) = schematic.get("Schematic") {
+        // version 3 wraps everything in another compound
+        read_sponge(sponge, sponge.get("Blocks").unwrap_or(sponge), &mut push)?;
+    } else {
+        read_sponge(schematic, schematic, &mut push)?;
+    }
+    Ok(blocks)
+}
+
+fn read_litematic(regions: &Tag, push: &mut impl FnMut(i32, i32, i32, Cell)) -> io::Result<()> {
+    let regions = regions.as_compound().ok_or_else(|| invalid("litematic without regions"))?;
+    for region in regions.values() {
+        let vector = |name: &str| -> io::Result<[i32; 3]> {
+            let vector = region.get(name);
+            let coord = |axis: &str| vector.and_then(|vector| vector.get(axis)).and_then(Tag::as_int);
+            match (coord("x"), coord("y"), coord("z")) {
+                (Some(x), Some(y), Some(z)) => Ok([x as i32, y as i32, z as i32]),
+                _ => Err(invalid("litematic region without position or size")),
+            }
+        };
+        let position = vector("Position")?;
+        let size = vector("Size")?;
+
+        // negative sizes extend the region from the position into the negative direction
+        let min = [0, 1, 2].map(|axis| position[axis] + (size[axis] + 1).min(0));
+        let [size_x, size_y, size_z] = size.map(|size| size.unsigned_abs() as usize);
+
+        let palette: Vec<Cell> = region
+            .get("BlockStatePalette")
+            .and_then(Tag::as_list)
+            .ok_or_else(|| invalid("litematic region without palette"))?
+            .iter()
+            .map(|entry| Cell::from_name(entry.get("Name").and_then(Tag::as_str).unwrap_or_default()))
+            .collect();
+        let states = region
+            .get("BlockStates")
+            .and_then(Tag::as_long_array)
+            .ok_or_else(|| invalid("litematic region without blocks"))?;
+
+        // litematica packs the entries tightly, they can span two longs
+        let bits = (usize::BITS - palette.len().saturating_sub(1).leading_zeros()).max(2) as usize;
+        // a broken size can overflow before the amount of blocks is compared
+        let needed_bits = [size_y, size_z, bits]
+            .into_iter()
+            .try_fold(size_x, |product, factor| product.checked_mul(factor));
+        if needed_bits.is_none_or(|needed_bits| states.len() * 64 < needed_bits) {
+            return Err(invalid("litematic region with broken block data"));
+        }
+        let entry = |index: usize| {
+            let start = index * bits;
+            let (long, offset) = (start / 64, start % 64);
+            let mut value = states[long] as u64 >> offset;
+            if offset + bits > 64 {
+                value |= (states[long + 1] as u64) << (64 - offset);
+            }
+            value & ((1 << bits) - 1)
+        };
+
+        for y in 0..size_y {
+            for z in 0..size_z {
+                for x in 0..size_x {
+                    let index = (y * size_z + z) * size_x + x;
+                    let cell = palette.get(entry(index) as usize).copied().unwrap_or(Cell::Unknown);
+                    push(min[0] + x as i32, min[1] + y as i32, min[2] + z as i32, cell);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_sponge(schematic: &Tag, blocks: &Tag, push: &mut impl FnMut(i32, i32, i32, Cell)) -> io::Result<()> {
+    let size = |name: &str| -> io::Result<usize> {
+        let size = schematic.get(name).and_then(Tag::as_int).ok_or_else(|| invalid("schematic without size"))?;
+        // sizes are unsigned shorts
+        Ok(size as u16 as usize)
+    };
+    let (width, height, length) = (size("Width")?, size("Height")?, size("Length")?);
+
+    let palette = blocks
+        .get("Palette")
+        .and_then(Tag::as_compound)
+        .ok_or_else(|| invalid("schematic without palette"))?;
+    let mut cells = vec![Cell::Unknown; palette.len()];
+    for (name, index) in palette {
+        let index = index.as_int().and_then(|index| usize::try_from(index).ok());
+        match index.and_then(|index| cells.get_mut(index)) {
+            Some(cell) => *cell = Cell::from_name(name),
+            None => return Err(invalid("schematic with broken palette")),
+        }
+    }
+
+    let data = blocks
+        .get("BlockData")
+        .or_else(|| blocks.get("Data"))
+        .and_then(Tag::as_byte_array)
+        .ok_or_else(|| invalid("schematic without blocks"))?;
+
+    // the palette indices are varints
+    let mut bytes = data.iter().map(|byte| *byte as u8);
+    for index in 0..width * height * length {
+        let mut value = 0usize;
+        for shift in (0..).step_by(7) {
+            let byte = bytes.next().ok_or_else(|| invalid("schematic with too few blocks"))?;
+            if shift > 28 {
+                return Err(invalid("schematic with broken block data"));
+            }
+            value |= ((byte & 0x7F) as usize) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        let (x, z, y) = (index % width, index / width % length, index / (width * length));
+        let cell = cells.get(value).copied().unwrap_or(Cell::Unknown);
+        push(x as i32, y as i32, z as i32, cell);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::BedrockGenerator;
+    use crate::import::nbt::tests::compound;
+    use crate::raw_data::modes::CrackerMode;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    const ORIGIN: (i32, i32, i32) = (10, 1, -3);
+    const SIZE: (i32, i32, i32) = (4, 5, 3);
+
+    fn name(name: &str) -> Tag {
+        Tag::String(name.to_string())
+    }
+
+    /// the blocks of the schematics in world coordinates, y=5 isnt imported
+    fn expected() -> Vec<Block> {
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let mut blocks = vec![];
+        for y in 1..=4 {
+            for z in ORIGIN.2..ORIGIN.2 + SIZE.2 {
+                for x in ORIGIN.0..ORIGIN.0 + SIZE.0 {
+                    blocks.push(Block::new(x, y, z, generator.block_at(x, y, z).unwrap_or(BlockType::OTHER)));
+                }
+            }
+        }
+        blocks.sort_by_key(|block| (block.x, block.y, block.z));
+        blocks
+    }
+
+    /// 0 is netherrack, 1 bedrock, in schematic coordinates
+    fn palette_indices() -> Vec<usize> {
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let mut indices = vec![];
+        for y in 0..SIZE.1 {
+            for z in 0..SIZE.2 {
+                for x in 0..SIZE.0 {
+                    let block = generator.block_at(ORIGIN.0 + x, ORIGIN.1 + y, ORIGIN.2 + z);
+                    indices.push((block == Some(BlockType::BEDROCK)) as usize);
+                }
+            }
+        }
+        indices
+    }
+
+    fn read(schematic: &Tag) -> Vec<Block> {
+        let mut blocks = parse_schematic(schematic, ORIGIN.0, ORIGIN.1, ORIGIN.2, BedrockProfile::NETHER).unwrap();
+        blocks.sort_by_key(|block| (block.x, block.y, block.z));
+        blocks
+    }
+
+    #[test]
+    fn test_sponge() {
+        let palette = compound([("minecraft:netherrack", Tag::Int(0)), ("minecraft:bedrock", Tag::Int(1))]);
+        let data = Tag::ByteArray(palette_indices().into_iter().map(|index| index as i8).collect());
+        let version2 = compound([
+            ("Width", Tag::Short(SIZE.0 as i16)),
+            ("Height", Tag::Short(SIZE.1 as i16)),
+            ("Length", Tag::Short(SIZE.2 as i16)),
+            ("Palette", palette.clone()),
+            ("BlockData", data.clone()),
+        ]);
+        assert_eq!(read(&version2), expected());
+
+        let version3 = compound([("Schematic", compound([
+            ("Width", Tag::Short(SIZE.0 as i16)),
+            ("Height", Tag::Short(SIZE.1 as i16)),
+            ("Length", Tag::Short(SIZE.2 as i16)),
+            ("Blocks", compound([("Palette", palette), ("Data", data)])),
+        ]))]);
+        assert_eq!(read(&version3), expected());
+    }
+
+    #[test]
+    fn test_litematic() {
+        // 5 palette entries need 3 bits per block, so some blocks span two longs
+        let mut states = vec![0i64; 3];
+        for (index, palette_index) in palette_indices().into_iter().enumerate() {
+            let value = palette_index as u64 + 3;
+            let (long, offset) = (index * 3 / 64, index * 3 % 64);
+            states[long] |= (value << offset) as i64;
+            if offset + 3 > 64 {
+                states[long + 1] |= (value >> (64 - offset)) as i64;
+            }
+        }
+        let vector = |x: i32, y: i32, z: i32| compound([("x", Tag::Int(x)), ("y", Tag::Int(y)), ("z", Tag::Int(z))]);
+        // the size points into the negative direction, the region still starts at the origin
+        let region = compound([
+            ("Position", vector(SIZE.0 - 1, 0, 0)),
+            ("Size", vector(-SIZE.0, SIZE.1, SIZE.2)),
+            ("BlockStatePalette", Tag::List(vec![
+                compound([("Name", name("minecraft:air"))]),
+                compound([("Name", name("minecraft:soul_sand"))]),
+                compound([("Name", name("minecraft:basalt[axis=y]"))]),
+                compound([("Name", name("minecraft:netherrack"))]),
+                compound([("Name", name("minecraft:bedrock"))]),
+            ])),
+            ("BlockStates", Tag::LongArray(states)),
+        ]);
+        let litematic = compound([("Regions", compound([("bedrock", region)]))]);
+        assert_eq!(read(&litematic), expected());
+
+        // a size that overflows the amount of blocks is an error, not a panic
+        let huge = compound([
+            ("Position", vector(0, 0, 0)),
+            ("Size", vector(1 << 30, 1 << 30, 1 << 30)),
+            ("BlockStatePalette", Tag::List(vec![compound([("Name", name("minecraft:bedrock"))])])),
+            ("BlockStates", Tag::LongArray(vec![0; 3])),
+        ]);
+        let litematic = compound([("Regions", compound([("bedrock", huge)]))]);
+        assert!(parse_schematic(&litematic, ORIGIN.0, ORIGIN.1, ORIGIN.2, BedrockProfile::NETHER).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Combines the results of separate roof and floor cracks, see OutputMode::BedrockSeed
+
+use std::collections::HashMap;
+use crate::MASK48;
+use crate::layer::{next_long, reverse_next_long};
+use crate::raw_data::modes::OutputMode;
+use crate::raw_data::profile::BedrockProfile;
+
+/// Reads a result file of the cracker, one seed per line with optional "(k mismatches)"
+pub fn parse_seed_list(list: &str) -> Result<Vec<(u64, u32)>, String> {
+    list.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_seed(line).ok_or_else(|| format!("line {}: not a seed: {}", i + 1, line.trim())))
+        .collect()
+}
+
+fn parse_seed(line: &str) -> Option<(u64, u32)> {
+    let (seed, mismatches) = match line.trim().split_once('(') {
+        Some((seed, rest)) => {
+            let mismatches = rest.strip_suffix(')')?.trim().strip_suffix("mismatches")?;
+            (seed, mismatches.trim().parse().ok()?)
+        }
+        None => (line, 0),
+    };
+    let seed = seed.trim();
+    // the cracker prints signed seeds, but unsigned ones are accepted too
+    let seed = seed.parse::<i64>().map(|seed| seed as u64).or_else(|_| seed.parse::<u64>()).ok()?;
+    Some((seed, mismatches))
+}
+
+/// Finds the seeds that produce a roof seed and a floor seed of the lists.
+/// The mismatches of both seeds are added up.
+/// BedrockSeed output keeps the roof seeds that have a matching floor seed
+pub fn intersect_bedrock_seeds(roof_seeds: &[(u64, u32)], floor_seeds: &[(u64, u32)], profile: BedrockProfile, output: OutputMode) -> Vec<(u64, u32)> {
+    let mut floor: HashMap<u64, u32> = HashMap::new();
+    for &(seed, mismatches) in floor_seeds {
+        let best = floor.entry(seed & MASK48).or_insert(mismatches);
+        *best = (*best).min(mismatches);
+    }
+
+    let mut results = vec![];
+    for &(roof_seed, roof_mismatches) in roof_seeds {
+        let roof_seed = roof_seed & MASK48;
+        for seed in reverse_next_long(roof_seed) {
+            // same steps as the end of the filter tree
+            let bedrock_seed = seed ^ profile.roof_hash;
+            let floor_seed = next_long(bedrock_seed ^ profile.floor_hash) & MASK48;
+            let Some(floor_mismatches) = floor.get(&floor_seed) else {
+                continue;
+            };
+            let mismatches = roof_mismatches + floor_mismatches;
+            if output == OutputMode::BedrockSeed {
+                results.push((roof_seed, mismatches));
+                continue;
+            }
+            for structure_seed in reverse_next_long(bedrock_seed) {
+                if output == OutputMode::WorldSeed {
+                    for prev_seed in reverse_next_long(structure_seed) {
+                        results.push((next_long(prev_seed), mismatches));
+                    }
+                } else {
+                    results.push((structure_seed, mismatches));
+                }
+            }
+        }
+    }
+    results.sort_unstable();
+    results.dedup();
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WORLD_SEED;
+    use super::*;
+
+    const ROOF_SEED: u64 = 191924403737289;
+    const FLOOR_SEED: u64 = 18240473916414;
+
+    #[test]
+    fn test_parse_seed_list() {
+        let list = "-5\n\n765906787396911863\n12 (2 mismatches)\n";
+        assert_eq!(parse_seed_list(list).unwrap(), vec![(-5i64 as u64, 0), (WORLD_SEED, 0), (12, 2)]);
+        assert_eq!(parse_seed_list("1\nseed\n").unwrap_err(), "line 2: not a seed: seed");
+    }
+
+    #[test]
+    fn test_intersect() {
+        let roof = [(ROOF_SEED - 1, 0), (ROOF_SEED, 1)];
+        let floor = [(FLOOR_SEED, 0), (FLOOR_SEED + 1, 0)];
+        let profile = BedrockProfile::NETHER;
+
+        let world_seeds = intersect_bedrock_seeds(&roof, &floor, profile, OutputMode::WorldSeed);
+        assert_eq!(world_seeds, vec![(WORLD_SEED, 1)]);
+        let structure_seeds = intersect_bedrock_seeds(&roof, &floor, profile, OutputMode::StructureSeed);
+        assert_eq!(structure_seeds, vec![(WORLD_SEED & MASK48, 1)]);
+        let roof_seeds = intersect_bedrock_seeds(&roof, &floor, profile, OutputMode::BedrockSeed);
+        assert_eq!(roof_seeds, vec![(ROOF_SEED, 1)]);
+        assert!(intersect_bedrock_seeds(&roof[..1], &floor, profile, OutputMode::WorldSeed).is_empty());
+    }
+}
@@ -2,26 +2,36 @@ use std::{array, fmt};
 
 use java_random::{JAVA_LCG, Random};
 use next_long_reverser::get_next_long;
-use crate::{CrackProgress, FLOOR_HASH, ROOF_HASH};
+use crate::CrackProgress;
 use crate::block_data::{BlockFilter, CheckObject, get_filter_power};
 use crate::raw_data::block::Block;
 use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
 use crate::raw_data::sender::Sender;
 
-fn split_floor_roof(blocks: &[Block], mode: CrackerMode) -> (Vec<BlockFilter>, Vec<BlockFilter>) {
+/// also returns the amount of blocks that contradict every seed
+pub(crate) fn split_floor_roof(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile) -> (Vec<BlockFilter>, Vec<BlockFilter>, u32) {
     let mut floor_blocks = vec![];
     let mut roof_blocks = vec![];
+    let mut impossible = 0;
 
     for block in blocks.iter() {
-        let filter = BlockFilter::from(block, mode);
-        if block.y < 64 {
+        // layers outside the gradients dont filter anything, they only catch misread or misaligned data
+        if let Some(block_type) = profile.fixed_block(block.y) {
+            if block.block_type != block_type {
+                impossible += 1;
+            }
+            continue;
+        }
+        let filter = BlockFilter::from(block, mode, profile);
+        if profile.is_floor(block.y) {
             floor_blocks.push(filter);
         } else {
             roof_blocks.push(filter);
         }
     }
 
-    (floor_blocks, roof_blocks)
+    (floor_blocks, roof_blocks, impossible)
 }
 
 #[derive(Debug, Clone)]
@@ -35,20 +45,29 @@ enum NextOperation<S: Sender> {
 struct CrossComparison<S: Sender> {
     sender: S,
     checks: Vec<CheckObject>,
-    //java hashes for minecraft:bedrock_floor and minecraft:bedrock_roof
+    //java hashes of the random names, usually minecraft:bedrock_floor and minecraft:bedrock_roof
     primary_hash: u64,
     secondary_hash: u64,
     output: OutputMode,
+    tolerance: u32,
+    /// mismatches every seed has
+    impossible: u32,
 }
 
-pub fn create_filter_tree<S: Sender>(blocks: &[Block], mode: CrackerMode, output: OutputMode, tx: S) -> Layer<S> {
+/// the surface that leaves fewer seeds is searched, the other one only gets checked at the end
+pub(crate) fn is_floor_primary(floor_blocks: &[BlockFilter], roof_blocks: &[BlockFilter]) -> bool {
+    get_filter_power(floor_blocks) < get_filter_power(roof_blocks)
+}
 
-    let (floor_blocks, roof_blocks) = split_floor_roof(blocks, mode);
+/// tolerance is the amount of blocks a seed may contradict and still be reported
+pub fn create_filter_tree<S: Sender>(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32, tx: S) -> Layer<S> {
 
-    let floor_resulting_seeds = get_filter_power(&floor_blocks);
-    let roof_resulting_seeds = get_filter_power(&roof_blocks);
+    let (floor_blocks, roof_blocks, impossible) = split_floor_roof(blocks, mode, profile);
+    // impossible blocks use up the tolerance of every seed
+    let unreachable = impossible > tolerance;
+    let tolerance = tolerance.saturating_sub(impossible);
 
-    let is_floor_primary_filter = floor_resulting_seeds < roof_resulting_seeds;
+    let is_floor_primary_filter = is_floor_primary(&floor_blocks, &roof_blocks);
 
     let (mut primary_filter, secondary_filter) = if is_floor_primary_filter {
         (floor_blocks, roof_blocks)
@@ -69,17 +88,20 @@ pub fn create_filter_tree<S: Sender>(blocks: &[Block], mode: CrackerMode, output
 
             checks.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
 
-            let res = checks
+            let mut res: Vec<_> = checks
                 .into_iter()
                 .map(|(_, a)| a.create_check(bits))
                 .collect();
+            if unreachable && bits == 12 {
+                res.push(CheckObject::failing());
+            }
 
-            Layer::new(bits, res)
+            Layer::new(bits, res, tolerance)
         })
         .collect();
 
     // add checks for the other surface
-    let final_check = CrossComparison::new(secondary_filter, tx, is_floor_primary_filter, output);
+    let final_check = CrossComparison::new(secondary_filter, tx, is_floor_primary_filter, profile, output, tolerance, impossible);
     if let Some(layer) = layers.last_mut() {
         layer.next_operation = NextOperation::CrossComparison(final_check);
     }
@@ -100,11 +122,12 @@ pub fn create_filter_tree<S: Sender>(blocks: &[Block], mode: CrackerMode, output
 pub struct Layer<S: Sender> {
     checks: Vec<[CheckObject; 8]>,
     split: u64,
+    tolerance: u32,
     next_operation: NextOperation<S>,
 }
 
 impl<S: Sender> Layer<S> {
-    fn new(lower_bits: u64, checks: Vec<CheckObject>) -> Self {
+    fn new(lower_bits: u64, checks: Vec<CheckObject>, tolerance: u32) -> Self {
         let split: u64 = 1 << (lower_bits.saturating_sub(1));
         let checks: Vec<[CheckObject; 8]> = checks
             .chunks(8)
@@ -120,15 +143,31 @@ impl<S: Sender> Layer<S> {
         Self {
             checks,
             split,
+            tolerance,
             next_operation: NextOperation::None,
         }
     }
 
     #[inline(always)]
     pub fn run_checks(&self, upper_bits: u64) {
-        if self.checks.iter().flatten().any(|check| check.check(upper_bits)) {
-            return;
-        }
+        // every block has at most one check per layer and a block that fails with fewer known bits
+        // also fails with more, so the mismatches of a single layer never overestimate the final amount
+        let mismatches = if self.tolerance == 0 {
+            if self.checks.iter().flatten().any(|check| check.check(upper_bits)) {
+                return;
+            }
+            0
+        } else {
+            let mismatches = self.checks
+                .iter()
+                .flatten()
+                .filter(|check| check.check(upper_bits))
+                .count() as u32;
+            if mismatches > self.tolerance {
+                return;
+            }
+            mismatches
+        };
 
         use std::borrow::Borrow;
         match self.next_operation.borrow() {
@@ -136,7 +175,7 @@ impl<S: Sender> Layer<S> {
                 layer.run_checks(upper_bits);
                 layer.run_checks(upper_bits + self.split);
             }
-            NextOperation::CrossComparison(checks) => checks.run(upper_bits),
+            NextOperation::CrossComparison(checks) => checks.run(upper_bits, mismatches),
             _ => {
                 panic!("No operation")
             }
@@ -144,23 +183,31 @@ impl<S: Sender> Layer<S> {
     }
 }
 
-fn reverse_next_long(seed: u64) -> Vec<u64> {
+pub(crate) fn reverse_next_long(seed: u64) -> Vec<u64> {
     get_next_long(seed)
         .into_iter()
         .map(|seed| seed ^ JAVA_LCG.multiplier)
         .collect()
 }
 
-fn next_long(seed: u64) -> u64 {
+pub(crate) fn next_long(seed: u64) -> u64 {
     Random::with_seed(seed).next_long() as u64
 }
 
+/// the seed of the positional random for one surface
+/// hash is the java hash of the random name, see BedrockProfile
+pub(crate) fn surface_seed(seed: u64, hash: u64) -> u64 {
+    next_long(next_long(seed) ^ hash)
+}
+
 impl<S: Sender> fmt::Debug for CrossComparison<S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("CrossComparison")
             .field("primary_hash", &self.primary_hash)
             .field("secondary_hash", &self.secondary_hash)
             .field("checks", &self.checks)
+            .field("tolerance", &self.tolerance)
+            .field("impossible", &self.impossible)
             .finish()
     }
 }
@@ -170,7 +217,10 @@ impl<S: Sender> CrossComparison<S> {
         blocks: Vec<BlockFilter>,
         sender: S,
         is_floor_primary_filter: bool,
+        profile: BedrockProfile,
         output: OutputMode,
+        tolerance: u32,
+        impossible: u32,
     ) -> CrossComparison<S> {
         let checks = blocks
             .into_iter()
@@ -178,9 +228,9 @@ impl<S: Sender> CrossComparison<S> {
             .collect();
 
         let (primary_hash, secondary_hash) = if is_floor_primary_filter {
-            (FLOOR_HASH, ROOF_HASH)
+            (profile.floor_hash, profile.roof_hash)
         } else {
-            (ROOF_HASH, FLOOR_HASH)
+            (profile.roof_hash, profile.floor_hash)
         };
 
         Self {
@@ -189,52 +239,75 @@ impl<S: Sender> CrossComparison<S> {
             primary_hash,
             secondary_hash,
             output,
+            tolerance,
+            impossible,
         }
     }
 
-    fn check(&self, seed: u64) -> bool {
+    /// returns the amount of mismatches or None if there are more than allowed
+    fn check(&self, seed: u64, allowed: u32) -> Option<u32> {
+        let mut mismatches = 0;
         for check in self.checks.iter() {
             if check.check(seed) {
-                return false;
+                mismatches += 1;
+                if mismatches > allowed {
+                    return None;
+                }
             }
         }
-        true
+        Some(mismatches)
     }
 
-    fn run(&self, seed: u64) {
-        reverse_next_long(seed)
+    fn run(&self, seed: u64, primary_mismatches: u32) {
+        let allowed = self.tolerance - primary_mismatches;
+        let bedrock_seeds = reverse_next_long(seed)
             .into_iter()
             .map(|seed| {
                 // get common bedrock seed
                 seed ^ self.primary_hash
             })
-            .filter(|bedrock_seed| {
+            .filter_map(|bedrock_seed| {
                 // filter with blocks from the other surface
                 let mut secondary_seed = bedrock_seed ^ self.secondary_hash;
                 secondary_seed = next_long(secondary_seed);
-                self.check(secondary_seed)
-            })
-            .flat_map(|bedrock_seed| {
+                self.check(secondary_seed, allowed)
+                    .map(|mismatches| (bedrock_seed, self.impossible + primary_mismatches + mismatches))
+            });
+
+        if self.output == OutputMode::BedrockSeed {
+            // stop at the surface seed, a seed without a next_long preimage cant be generated by the game
+            if let Some(mismatches) = bedrock_seeds.map(|(_, mismatches)| mismatches).min() {
+                self.sender.send(CrackProgress::result(seed, mismatches));
+            }
+            return;
+        }
+
+        bedrock_seeds
+            .flat_map(|(bedrock_seed, mismatches)| {
                 // reverse to world seed & mask48 aka structure seed
                 reverse_next_long(bedrock_seed)
+                    .into_iter()
+                    .map(move |structure_seed| (structure_seed, mismatches))
             })
-            .for_each(|structure_seed| {
+            .for_each(|(structure_seed, mismatches)| {
                 if self.output == OutputMode::WorldSeed {
                     for prev_seed in reverse_next_long(structure_seed) {
                         let world_seed = next_long(prev_seed);
-                        self.sender.send(CrackProgress::Seed(world_seed));
+                        self.sender.send(CrackProgress::result(world_seed, mismatches));
                     }
                 } else {
-                    self.sender.send(CrackProgress::Seed(structure_seed));
+                    self.sender.send(CrackProgress::result(structure_seed, mismatches));
                 }
             });
     }
 }
 
 #[cfg(test)]
+// test_checks panics inside for_each on purpose
+#[allow(clippy::never_loop)]
 mod tests {
     use std::sync::mpsc;
-    use crate::MASK48;
+    use crate::{FLOOR_HASH, MASK48, ROOF_HASH};
     use crate::raw_data::block_type::BlockType;
     use super::*;
 
@@ -292,14 +365,14 @@ mod tests {
         BLOCKS
             .iter()
             .filter(|block| block.y > 5)
-            .map(|block| BlockFilter::from(block, CrackerMode::Normal).create_check(10))
+            .map(|block| BlockFilter::from(block, CrackerMode::Normal, BedrockProfile::NETHER).create_check(10))
             .filter(|check| check.check(ROOF_SEED & 0xFFFF_FFFF_FC00))
             .for_each(|check| panic!("roof bedrock failed: {:#?}", check));
 
         BLOCKS
             .iter()
             .filter(|block| block.y < 5)
-            .map(|block| BlockFilter::from(block, CrackerMode::Normal).create_check(10))
+            .map(|block| BlockFilter::from(block, CrackerMode::Normal, BedrockProfile::NETHER).create_check(10))
             .filter(|check| check.check(FLOOR_SEED & 0xFFFF_FFFF_FC00))
             .for_each(|check| panic!("floor bedrock failed: {:#?}", check));
     }
@@ -308,7 +381,7 @@ mod tests {
     fn test_filter_tree() {
         let (sender, receiver) = mpsc::channel();
 
-        let layers = create_filter_tree(&BLOCKS, CrackerMode::Normal, OutputMode::WorldSeed, sender);
+        let layers = create_filter_tree(&BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, sender);
 
         // the cracker uses roof data as the primary filter if it has equal info from floor and roof
         layers.run_checks(ROOF_SEED & 0xFFFF_FFFF_F000);
@@ -320,5 +393,57 @@ mod tests {
             panic!("No seed found")
         }
     }
-}
 
+    #[test]
+    fn test_filter_tree_tolerance() {
+        let mut blocks = BLOCKS.to_vec();
+        // one misread block on each surface
+        blocks[0].block_type = BlockType::BEDROCK;
+        blocks[12].block_type = BlockType::BEDROCK;
+
+        for (tolerance, expected) in [(1, vec![]), (2, vec![WORLD_SEED])] {
+            let (sender, receiver) = mpsc::channel();
+            let layers = create_filter_tree(&blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, tolerance, sender);
+            layers.run_checks(ROOF_SEED & 0xFFFF_FFFF_F000);
+            drop(layers);
+
+            let results: Vec<_> = receiver
+                .iter()
+                .map(|progress| match progress {
+                    CrackProgress::PartialMatch(seed, 2) => seed,
+                    _ => panic!("Unexpected result {:?}", progress),
+                })
+                .collect();
+            assert_eq!(results, expected);
+        }
+    }
+
+    #[test]
+    fn test_solid_layers() {
+        let mut blocks = BLOCKS.to_vec();
+        blocks.push(Block::new(3, 0, 3, BlockType::BEDROCK));
+        blocks.push(Block::new(3, 127, 3, BlockType::BEDROCK));
+        blocks.push(Block::new(4, 0, 4, BlockType::OTHER));
+
+        for (tolerance, expected) in [(0, vec![]), (1, vec![CrackProgress::PartialMatch(WORLD_SEED, 1)])] {
+            let (sender, receiver) = mpsc::channel();
+            let layers = create_filter_tree(&blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, tolerance, sender);
+            layers.run_checks(ROOF_SEED & 0xFFFF_FFFF_F000);
+            drop(layers);
+            assert_eq!(receiver.iter().collect::<Vec<_>>(), expected);
+        }
+    }
+
+    #[test]
+    fn test_bedrock_seed_output() {
+        // only roof data, the floor seed cant be known yet
+        let roof: Vec<_> = BLOCKS.iter().filter(|block| block.y > 5).cloned().collect();
+        let (sender, receiver) = mpsc::channel();
+        let layers = create_filter_tree(&roof, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::BedrockSeed, 0, sender);
+        layers.run_checks(ROOF_SEED & 0xFFFF_FFFF_F000);
+        drop(layers);
+        // a dozen blocks dont pin down the lowest bits
+        let results: Vec<_> = receiver.iter().collect();
+        assert!(results.contains(&CrackProgress::Seed(ROOF_SEED)));
+    }
+}
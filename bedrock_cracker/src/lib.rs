@@ -1,65 +1,428 @@
+pub mod benchmark;
 mod block_data;
+pub mod checkpoint;
+pub mod distributed;
+pub mod estimate;
+pub mod generator;
+pub mod handle;
+pub mod import;
+pub mod intersect;
+pub mod overworld;
 mod layer;
+pub mod verification;
 pub mod raw_data;
+pub mod suggest;
 
-use std::cmp::min;
+use std::cmp::{max, min};
+use std::ops::Range;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-use std::{thread};
 
 
-
-use crate::block_data::{BlockFilter, get_filter_power};
-use crate::layer::{create_filter_tree};
+use crate::block_data::{known_ranges, BlockFilter};
+use crate::checkpoint::Checkpoint;
+use crate::handle::{CrackHandle, SearchState};
+use crate::layer::{create_filter_tree, is_floor_primary, split_floor_roof, Layer};
 use crate::raw_data::block::Block;
 use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
 use crate::raw_data::sender::Sender;
 
 const MASK48: u64 = 0xFFFF_FFFF_FFFF;
+/// the seed the benchmark and the tests generate their bedrock from
+const WORLD_SEED: u64 = 765906787396911863;
 const ROOF_HASH: u64 = 343340730;
 const FLOOR_HASH: u64 = 2042456806;
 
 const CHUNK_SIZE: u64 = (1 << 12) * (1 << 25); // interrupts every 2^25 seeds
+const CHUNK_COUNT: u64 = (1 << 48) / CHUNK_SIZE;
+const CANCEL_INTERVAL: u64 = (1 << 12) * (1 << 10); // checks for cancellation every 2^10 seeds
+
+/// every upper bits value covers 2^12 seeds of the 2^48 search space
+pub const SEARCH_SPACE: Range<u64> = 0..1 << 48;
+
+/// whether OutputMode::BedrockSeed reports seeds of the floor, otherwise they belong to the roof
+pub fn reports_floor_seeds(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile) -> bool {
+    let (floor_blocks, roof_blocks, _) = split_floor_roof(blocks, mode, profile);
+    is_floor_primary(&floor_blocks, &roof_blocks)
+}
+
+/// how many bits of the 48 bit surface seed a block reveals on its own.
+/// Layers that look the same for every seed reveal nothing
+pub fn block_information(block: &Block, mode: CrackerMode, profile: BedrockProfile) -> f64 {
+    if profile.fixed_block(block.y).is_some() {
+        return 0.0;
+    }
+    let discarded = BlockFilter::from(block, mode, profile).discarded_seeds(0);
+    -(1.0 - discarded).log2()
+}
+
+/// bits known about the floor seed and the roof seed, a surface needs about 48 to be cracked on its own.
+/// Blocks with the same random, like a column in paper mode, only count what they tell together
+pub fn surface_information(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile) -> (f64, f64) {
+    known_ranges(blocks, mode, profile).iter().fold((0.0, 0.0), |(floor, roof), ((is_floor, _), (lower, upper))| {
+        // contradicting blocks are marked as impossible instead
+        let bits = match upper.saturating_sub(*lower) {
+            0 => 0.0,
+            possible => -(possible as f64 / MASK48 as f64).log2(),
+        };
+        if *is_floor {
+            (floor + bits, roof)
+        } else {
+            (floor, roof + bits)
+        }
+    })
+}
+
+/// tolerance is the amount of blocks a seed may contradict, those seeds get reported as partial matches
+pub fn search_bedrock_pattern<S: Sender + 'static>(blocks: &[Block], thread_count: u64, mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32, sender: S) -> CrackHandle {
+    search_bedrock_range(blocks, thread_count, mode, profile, output, tolerance, SEARCH_SPACE, sender)
+}
+
+/// Same as search_bedrock_pattern but only searches the upper bits in range.
+/// Both ends of the range have to be multiples of 2^12, see shard_range
+#[allow(clippy::too_many_arguments)]
+pub fn search_bedrock_range<S: Sender + 'static>(blocks: &[Block], thread_count: u64, mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32, range: Range<u64>, sender: S) -> CrackHandle {
+    let checks = create_filter_tree(blocks, mode, profile, output, tolerance, sender.clone());
+    run_search(checks, thread_count, range, None, sender)
+}
+
+/// Same as search_bedrock_range over the range of the checkpoint but skips the chunks that
+/// the checkpoint marks as finished. The progress of finished chunks is reported first.
+/// Every chunk that finishes is reported with CrackProgress::ChunkDone after all of its seeds,
+/// the caller marks it as finished once it saved those seeds
+#[allow(clippy::too_many_arguments)]
+pub fn resume_bedrock_search<S: Sender + 'static>(blocks: &[Block], thread_count: u64, mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32, checkpoint: &Checkpoint, sender: S) -> CrackHandle {
+    let checks = create_filter_tree(blocks, mode, profile, output, tolerance, sender.clone());
+    run_search(checks, thread_count, checkpoint.range(), Some(checkpoint), sender)
+}
 
-/// this estimate is naive
-pub fn estimate_result_amount(blocks: &[Block]) -> u64 {
-    let filters: Vec<_> = blocks.iter()
-        .map(|block | BlockFilter::from(block, CrackerMode::Normal))
-        .collect();
-    get_filter_power(&filters)
+/// The upper bits of shard i out of shard_count equally sized shards of the search space
+pub fn shard_range(shard: u64, shard_count: u64) -> Range<u64> {
+    assert!(shard < shard_count, "shard {shard} doesnt exist with {shard_count} shards");
+    let start = (shard * (1 << 36)) / shard_count;
+    let end = ((shard + 1) * (1 << 36)) / shard_count;
+    start << 12..end << 12
 }
 
-pub fn search_bedrock_pattern<S: Sender + 'static>(blocks: &[Block], thread_count: u64, mode: CrackerMode, output: OutputMode, sender: S) {
-    let checks = create_filter_tree(blocks, mode, output, sender.clone());
+fn run_search<S: Sender + 'static>(checks: Layer<S>, thread_count: u64, range: Range<u64>, checkpoint: Option<&Checkpoint>, sender: S) -> CrackHandle {
+    assert!(range.end <= SEARCH_SPACE.end, "range exceeds the search space");
+    assert!((range.start | range.end) & 0xFFF == 0, "range isnt aligned to 2^12");
 
-    for thread in 0..thread_count {
-        let mut start_bits = (thread * (1 << 36)) / thread_count;
-        let mut end_bits = ((thread + 1) * (1 << 36)) / thread_count;
-        start_bits <<= 12;
-        end_bits <<= 12;
+    // chunks are aligned to the whole search space so checkpoints dont depend on the thread count
+    let (finished, chunks): (Vec<_>, Vec<_>) = (range.start / CHUNK_SIZE..range.end.div_ceil(CHUNK_SIZE))
+        .map(|chunk| {
+            let start_bits = max(chunk * CHUNK_SIZE, range.start);
+            let end_bits = min((chunk + 1) * CHUNK_SIZE, range.end);
+            (chunk, start_bits..end_bits)
+        })
+        .partition(|(chunk, _)| checkpoint.is_some_and(|checkpoint| checkpoint.is_finished(*chunk)));
 
+    let finished: u64 = finished.into_iter().map(|(_, bits)| bits.end - bits.start).sum();
+    if finished != 0 && !sender.send(CrackProgress::Progress(finished)) {
+        return CrackHandle::cancelled();
+    }
+    let report_chunks = checkpoint.is_some();
+
+    // every thread searches its own slice of every chunk, so a few chunks still keep all threads busy.
+    // the last thread that finishes its slice of a chunk reports the chunk as done
+    let chunks = Arc::new(chunks);
+    let remaining: Arc<Vec<_>> = Arc::new(chunks.iter().map(|_| AtomicU64::new(thread_count)).collect());
+
+    let jobs = (0..thread_count).map(|thread| {
         let checks = checks.clone();
 
         let sender = sender.clone();
+        let chunks = chunks.clone();
+        let remaining = remaining.clone();
 
-        thread::spawn(move || {
-            while start_bits < end_bits {
-                let chunk_end = min(start_bits + CHUNK_SIZE, end_bits);
-                for upper_bits in (start_bits..chunk_end).step_by(1 << 12) {
+        move |state: &SearchState| {
+            for ((chunk, bits), remaining) in chunks.iter().zip(remaining.iter()) {
+                let slice = thread_slice(bits, thread, thread_count);
+                for upper_bits in slice.clone().step_by(1 << 12) {
+                    // an unfinished chunk isnt marked as done, so it gets searched again after a resume
+                    if (upper_bits == slice.start || upper_bits % CANCEL_INTERVAL == 0) && state.should_stop() {
+                        return;
+                    }
                     checks.run_checks(upper_bits);
                 }
                 //dropping the receiver stops the threads
-                if !sender.send(CrackProgress::Progress(chunk_end - start_bits)) {
+                if !slice.is_empty() && !sender.send(CrackProgress::Progress(slice.end - slice.start)) {
+                    state.cancel();
+                    return;
+                }
+                // the seeds of the other slices were sent before their threads counted down
+                if remaining.fetch_sub(1, Ordering::AcqRel) == 1 && report_chunks && !sender.send(CrackProgress::ChunkDone(*chunk)) {
+                    state.cancel();
                     return;
                 }
-
-                start_bits = chunk_end;
             }
-        });
-    }
+        }
+    });
+    CrackHandle::spawn(jobs.collect(), sender)
+}
+
+/// The part of the upper bits of a chunk that one of thread_count threads searches
+fn thread_slice(bits: &Range<u64>, thread: u64, thread_count: u64) -> Range<u64> {
+    let steps = (bits.end - bits.start) >> 12;
+    let start = bits.start + ((thread * steps / thread_count) << 12);
+    let end = bits.start + (((thread + 1) * steps / thread_count) << 12);
+    start..end
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CrackProgress {
     Seed(u64),
+    /// a seed and the amount of blocks it contradicts
+    PartialMatch(u64, u32),
     Progress(u64),
+    /// every seed of the chunk was sent, only reported by resume_bedrock_search, see Checkpoint::finish
+    ChunkDone(u64),
+    /// the whole range was searched, always the last event
+    Finished,
+    /// the search stopped early, always the last event
+    Cancelled,
+}
+
+impl CrackProgress {
+    fn result(seed: u64, mismatches: u32) -> CrackProgress {
+        if mismatches == 0 {
+            CrackProgress::Seed(seed)
+        } else {
+            CrackProgress::PartialMatch(seed, mismatches)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::generator::BedrockGenerator;
+    use crate::handle::CrackOutcome;
+    use crate::raw_data::block_type::BlockType;
+    use crate::layer::surface_seed;
+    use crate::raw_data::profile::random_hash;
+    use crate::benchmark::benchmark_blocks;
+    use super::*;
+
+    #[test]
+    fn test_information() {
+        let profile = BedrockProfile::NETHER;
+        let bits = |y, block_type| block_information(&Block::new(3, y, 7, block_type), CrackerMode::Normal, profile);
+        // bedrock on y=4 and y=123 has a 20% chance, on y=1 an 80% chance
+        assert!((bits(4, BlockType::BEDROCK) - 5f64.log2()).abs() < 1e-6);
+        assert!((bits(1, BlockType::BEDROCK) - 1.25f64.log2()).abs() < 1e-6);
+        assert!((bits(123, BlockType::BEDROCK) - 5f64.log2()).abs() < 1e-6);
+        assert_eq!(bits(0, BlockType::BEDROCK), 0.0);
+
+        let blocks = [
+            Block::new(0, 4, 0, BlockType::BEDROCK),
+            Block::new(1, 4, 0, BlockType::BEDROCK),
+            Block::new(0, 123, 0, BlockType::BEDROCK),
+        ];
+        let (floor, roof) = surface_information(&blocks, CrackerMode::Normal, profile);
+        assert!((floor - 2.0 * 5f64.log2()).abs() < 1e-6);
+        assert!((roof - 5f64.log2()).abs() < 1e-6);
+
+        // the same block twice tells nothing new
+        let twice = [Block::new(0, 4, 0, BlockType::BEDROCK), Block::new(0, 4, 0, BlockType::BEDROCK)];
+        let (floor, _) = surface_information(&twice, CrackerMode::Normal, profile);
+        assert!((floor - 5f64.log2()).abs() < 1e-6);
+
+        // paper uses one random for the column, bedrock on y=4 already means bedrock on y=3
+        let column = [Block::new(0, 4, 0, BlockType::BEDROCK), Block::new(0, 3, 0, BlockType::BEDROCK)];
+        let (floor, _) = surface_information(&column, CrackerMode::Paper1_18, profile);
+        assert!((floor - 5f64.log2()).abs() < 1e-6);
+        let (floor, _) = surface_information(&column, CrackerMode::Normal, profile);
+        assert!((floor - 5f64.log2() - 2.5f64.log2()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_custom_profile() {
+        // a datapack nether with a deeper floor, a thicker roof and its own surface rules
+        let profile = BedrockProfile {
+            floor_y: -64,
+            roof_y: 180,
+            depth: 8,
+            floor_hash: random_hash("datapack:deep_floor"),
+            roof_hash: random_hash("datapack:thick_roof"),
+        };
+        let generator = BedrockGenerator::with_profile(WORLD_SEED, CrackerMode::Normal, profile);
+        let blocks: Vec<_> = generator
+            .generate_area(0, 0, 3, 3)
+            .into_iter()
+            .filter(|block| !profile.is_solid(block.y))
+            .collect();
+        assert!(blocks.iter().any(|block| block.y == -58));
+        assert!(blocks.iter().any(|block| block.y == 187));
+
+        // the roof has more data so it is searched first
+        let upper_bits = surface_seed(WORLD_SEED, profile.roof_hash) & MASK48 & !0xFFF;
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_range(&blocks, 1, CrackerMode::Normal, profile, OutputMode::WorldSeed, 0, upper_bits..upper_bits + (1 << 12), sender);
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+        assert!(receiver.iter().any(|event| event == CrackProgress::Seed(WORLD_SEED)));
+
+        // the vanilla layers dont match
+        let (sender, receiver) = mpsc::channel();
+        search_bedrock_range(&blocks, 1, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, upper_bits..upper_bits + (1 << 12), sender);
+        assert!(!receiver.iter().any(|event| event == CrackProgress::Seed(WORLD_SEED)));
+    }
+
+    #[test]
+    fn test_search_range() {
+        let blocks = benchmark_blocks();
+
+        // the roof has more data so it is searched first
+        let upper_bits = surface_seed(WORLD_SEED, ROOF_HASH) & MASK48 & !0xFFF;
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_range(&blocks, 2, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, upper_bits..upper_bits + (1 << 12), sender);
+
+        let events: Vec<_> = receiver.iter().collect();
+        assert!(matches!(events.last(), Some(CrackProgress::Finished)));
+
+        let mut progress = 0;
+        let mut seeds = vec![];
+        for event in events {
+            match event {
+                CrackProgress::Seed(seed) => seeds.push(seed),
+                CrackProgress::Progress(num) => progress += num,
+                CrackProgress::PartialMatch(_, _) => panic!("No tolerance was given"),
+                CrackProgress::ChunkDone(_) => panic!("No checkpoint was given"),
+                CrackProgress::Finished => {}
+                CrackProgress::Cancelled => panic!("Nothing cancelled the search"),
+            }
+        }
+        assert_eq!(seeds, vec![WORLD_SEED]);
+        assert_eq!(progress, 1 << 12);
+        assert!(handle.is_finished());
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_pattern(&benchmark_blocks(), 2, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, sender);
+        handle.cancel();
+        assert!(matches!(receiver.iter().last(), Some(CrackProgress::Cancelled)));
+        assert_eq!(handle.join(), CrackOutcome::Cancelled);
+    }
+
+    #[test]
+    fn test_pause() {
+        let range = 0..CANCEL_INTERVAL * 4;
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_range(&benchmark_blocks(), 2, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::StructureSeed, 0, range.clone(), sender);
+        let token = handle.token();
+        token.pause();
+        thread::sleep(Duration::from_millis(20));
+        token.resume();
+
+        let progress: u64 = receiver.iter().map(|event| match event {
+            CrackProgress::Progress(num) => num,
+            _ => 0,
+        }).sum();
+        assert_eq!(progress, range.end);
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_pattern(&benchmark_blocks(), 2, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, sender);
+        handle.pause();
+        handle.cancel();
+        assert!(matches!(receiver.iter().last(), Some(CrackProgress::Cancelled)));
+        assert_eq!(handle.join(), CrackOutcome::Cancelled);
+    }
+
+    #[test]
+    fn test_threads_share_chunks() {
+        // fewer chunks than threads, every thread still gets an equal part
+        let range = 0..8 << 12;
+        for thread in 0..4 {
+            assert_eq!(thread_slice(&range, thread, 4), thread * (2 << 12)..(thread + 1) * (2 << 12));
+        }
+        let (sender, receiver) = mpsc::channel();
+        let handle = search_bedrock_range(&benchmark_blocks(), 4, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed, 0, range, sender);
+        let progress: Vec<_> = receiver
+            .iter()
+            .filter_map(|event| match event {
+                CrackProgress::Progress(num) => Some(num),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress, vec![2 << 12; 4]);
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+    }
+
+    #[test]
+    fn test_resume_after_kill() {
+        let blocks = [Block::new(0, 123, 0, BlockType::BEDROCK), Block::new(1, 123, 0, BlockType::BEDROCK)];
+        let search = |checkpoint: &Checkpoint| {
+            let (sender, receiver) = mpsc::channel();
+            let handle = resume_bedrock_search(&blocks, 2, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::BedrockSeed, 0, checkpoint, sender);
+            (handle, receiver)
+        };
+        // the end of one chunk and the start of the next
+        let range = CHUNK_SIZE - (4 << 12)..CHUNK_SIZE + (4 << 12);
+        let path = std::env::temp_dir().join(format!("bedrock_resume_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let open = || Checkpoint::open(&path, &blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::BedrockSeed, 0, range.clone()).unwrap();
+
+        let (sender, receiver) = mpsc::channel();
+        search_bedrock_range(&blocks, 1, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::BedrockSeed, 0, range.clone(), sender);
+        let mut expected: Vec<_> = receiver.iter().filter_map(|event| match event {
+            CrackProgress::Seed(seed) => Some(seed),
+            _ => None,
+        }).collect();
+        expected.sort_unstable();
+        assert!(!expected.is_empty());
+
+        // like the cli, seeds are only saved when a chunk is done, the search gets killed after that
+        let mut checkpoint = open();
+        let (handle, receiver) = search(&checkpoint);
+        let mut saved = vec![];
+        let mut unsaved = vec![];
+        for event in receiver {
+            match event {
+                CrackProgress::Seed(_) if checkpoint.finished_chunks() != 0 => break,
+                CrackProgress::Seed(seed) => unsaved.push(seed),
+                CrackProgress::ChunkDone(chunk) => {
+                    saved.append(&mut unsaved);
+                    checkpoint.finish(chunk).unwrap();
+                }
+                _ => {}
+            }
+        }
+        handle.cancel();
+        handle.join();
+
+        let checkpoint = open();
+        assert_eq!(checkpoint.finished_chunks(), 1);
+        let (handle, receiver) = search(&checkpoint);
+        for event in receiver {
+            match event {
+                CrackProgress::Seed(seed) => saved.push(seed),
+                CrackProgress::ChunkDone(chunk) => assert!(!checkpoint.is_finished(chunk)),
+                _ => {}
+            }
+        }
+        assert_eq!(handle.join(), CrackOutcome::Finished);
+        saved.sort_unstable();
+        saved.dedup();
+        assert_eq!(saved, expected);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_shard_range() {
+        let shards: Vec<_> = (0..7).map(|shard| shard_range(shard, 7)).collect();
+        assert_eq!(shards[0].start, SEARCH_SPACE.start);
+        assert_eq!(shards[6].end, SEARCH_SPACE.end);
+        for pair in shards.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+            assert_eq!(pair[0].end & 0xFFF, 0);
+        }
+    }
 }
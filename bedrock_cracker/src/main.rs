@@ -0,0 +1,498 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::net::TcpListener;
+use std::ops::Range;
+use std::process::exit;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::{env, thread};
+
+use bedrock_cracker::raw_data::config::parse_config;
+use bedrock_cracker::overworld::search_overworld_pattern;
+use bedrock_cracker::raw_data::modes::{CrackerMode, Dimension, OutputMode};
+use bedrock_cracker::raw_data::profile::{random_hash, BedrockProfile};
+use bedrock_cracker::checkpoint::Checkpoint;
+use bedrock_cracker::benchmark::{benchmark, Calibration};
+use bedrock_cracker::estimate::estimate_crack;
+use bedrock_cracker::distributed::{run_coordinator, run_worker};
+use bedrock_cracker::handle::CrackOutcome;
+use bedrock_cracker::import::anvil::read_nether;
+use bedrock_cracker::import::schematic::read_schematic;
+use bedrock_cracker::{reports_floor_seeds, resume_bedrock_search, search_bedrock_range, shard_range, CrackProgress, SEARCH_SPACE};
+
+const USAGE: &str = "\
+Usage: bedrock_cracker [OPTIONS] <BLOCK_FILE>
+       bedrock_cracker [OPTIONS] --world <WORLD>
+       bedrock_cracker [OPTIONS] --schematic <FILE> --origin <X,Y,Z>
+       bedrock_cracker --worker <ADDR> [-t <N>] [--token <TOKEN>]
+       bedrock_cracker --benchmark [-t <N>] [--calibration <PATH>]
+
+Reads a block list in the format written by the gui (\"x y z Bedrock|Other|Deepslate\" per line)
+and prints every matching seed.
+
+Options:
+  -t, --threads <N>     Number of worker threads [default: all cores]
+  -d, --dimension <D>   nether | overworld [default: nether]
+  -m, --mode <MODE>     normal | paper1_18 [default: normal]
+  -o, --output <MODE>   world | structure | bedrock [default: world]
+                        bedrock stops at the seed of one surface, e.g. to crack the roof now
+                        and combine it with floor data later
+  -s, --structure-seed <SEED>
+                        Overworld only: search the 2^16 world seeds of this structure seed
+                        instead of all 2^64 world seeds
+  -k, --tolerance <K>   Also report seeds that contradict up to K blocks [default: 0]
+  -f, --file <PATH>     Write seeds to a file instead of stdout
+  --shard <I/N>         Nether only: search only shard I (0 based) of N equally sized shards.
+                        The results of all shards together are the results of the full search
+  -c, --checkpoint <PATH>
+                        Nether only: remember finished parts of the search in this file and
+                        skip them when the same crack is started again. Seeds are appended
+                        to the --file
+  --coordinator <ADDR>  Nether only: listen on ADDR (e.g. 0.0.0.0:7878) and split the search
+                        over the workers that connect instead of searching locally
+  --worker <ADDR>       Connect to the coordinator at ADDR and search the parts it hands out.
+                        The blocks and settings come from the coordinator
+  --token <TOKEN>       Secret that workers need to join the coordinator. Without it anyone who
+                        can reach the coordinator can send results, only use it on a trusted network
+  -w, --world <WORLD>   Nether only: read the blocks from the region files of a world folder
+                        instead of a block file
+  --area <X1,Z1,X2,Z2>  The area that --world reads [default: 0,0,3,3]
+  --schematic <FILE>    Nether only: read the blocks from a .litematic or .schem file
+  --origin <X,Y,Z>      The world position the schematic was saved at
+  --floor-y <Y>         Nether only: the floor is bedrock at and below Y, for worlds with
+                        custom noise settings [default: 0]
+  --roof-y <Y>          Nether only: the roof has no bedrock at Y, bedrock starts above it
+                        [default: 122]
+  --gradient-depth <N>  Nether only: amount of layers the bedrock gradients span [default: 5]
+  --floor-random <NAME> Nether only: random name of the floor gradient rule
+                        [default: minecraft:bedrock_floor]
+  --roof-random <NAME>  Nether only: random name of the roof gradient rule
+                        [default: minecraft:bedrock_roof]
+  --benchmark           Measure how fast 1 and --threads threads search instead of cracking
+  --calibration <PATH>  Where --benchmark saves its measurements, a crack reads them from there
+                        to predict its runtime
+  -h, --help            Print this message";
+
+/// per thread count of --benchmark
+const BENCHMARK_DURATION: Duration = Duration::from_secs(3);
+
+struct Args {
+    block_file: Option<String>,
+    threads: u64,
+    dimension: Dimension,
+    mode: CrackerMode,
+    output: OutputMode,
+    structure_seed: Option<u64>,
+    range: Range<u64>,
+    tolerance: u32,
+    seed_file: Option<String>,
+    checkpoint: Option<String>,
+    coordinator: Option<String>,
+    worker: Option<String>,
+    token: String,
+    world: Option<String>,
+    area: [i32; 4],
+    schematic: Option<(String, [i32; 3])>,
+    profile: BedrockProfile,
+    benchmark: bool,
+    calibration: Option<String>,
+}
+
+fn main() {
+    let args = match parse_args(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n\n{USAGE}");
+            exit(2);
+        }
+    };
+
+    if let Some(address) = &args.worker {
+        eprintln!("Working for {address} on {} threads", args.threads);
+        if let Err(err) = run_worker(address.as_str(), args.threads, &args.token) {
+            eprintln!("Lost the coordinator: {err}");
+            exit(1);
+        }
+        eprintln!("Search finished");
+        return;
+    }
+
+    if args.benchmark {
+        let mut calibration = Calibration::default();
+        for threads in [1, args.threads] {
+            if calibration.measurements.iter().any(|(measured, _)| *measured == threads) {
+                continue;
+            }
+            let speed = benchmark(threads, BENCHMARK_DURATION);
+            eprintln!("{threads} threads: {speed:.3e} seeds/s");
+            calibration.measurements.push((threads, speed));
+        }
+        if let Some(path) = &args.calibration {
+            if let Err(err) = fs::write(path, calibration.to_string()) {
+                eprintln!("Couldnt save the calibration to {path}: {err}");
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    let calibration = match &args.calibration {
+        Some(path) => match fs::read_to_string(path).map_err(|err| err.to_string()).and_then(|file| Calibration::parse(&file)) {
+            Ok(calibration) => calibration,
+            Err(err) => {
+                eprintln!("Couldnt read the calibration {path}: {err}");
+                exit(1);
+            }
+        },
+        None => Calibration::default(),
+    };
+
+    let blocks = match (&args.world, &args.schematic, &args.block_file) {
+        (_, Some((schematic, [x, y, z])), _) => match read_schematic(schematic, *x, *y, *z, args.profile) {
+            Ok(blocks) if blocks.is_empty() => {
+                eprintln!("{schematic} has no blocks on the bedrock layers");
+                exit(1);
+            }
+            Ok(blocks) => blocks,
+            Err(err) => {
+                eprintln!("Couldnt read {schematic}: {err}");
+                exit(1);
+            }
+        },
+        (Some(world), _, _) => {
+            let [x1, z1, x2, z2] = args.area;
+            match read_nether(world, x1, z1, x2, z2, args.profile) {
+                Ok(blocks) if blocks.is_empty() => {
+                    eprintln!("{world} has no generated nether chunks between {x1} {z1} and {x2} {z2}");
+                    exit(1);
+                }
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    eprintln!("Couldnt read {world}: {err}");
+                    exit(1);
+                }
+            }
+        }
+        (None, None, Some(block_file)) => {
+            let config = match fs::read_to_string(block_file) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("Couldnt read {block_file}: {err}");
+                    exit(1);
+                }
+            };
+            let (blocks, errors) = parse_config(&config, args.dimension, args.profile);
+            if !errors.is_empty() {
+                for error in errors {
+                    eprintln!("{block_file}: {error}");
+                }
+                exit(1);
+            }
+            blocks
+        }
+        (None, None, None) => {
+            eprintln!("Missing block file\n\n{USAGE}");
+            exit(2);
+        }
+    };
+
+    let mut writer: Box<dyn Write> = match &args.seed_file {
+        Some(path) => match OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(args.checkpoint.is_some())
+            .truncate(args.checkpoint.is_none())
+            .open(path)
+        {
+            Ok(file) => Box::new(BufWriter::new(file)),
+            Err(err) => {
+                eprintln!("Couldnt create {path}: {err}");
+                exit(1);
+            }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+
+    let (sender, receiver) = mpsc::channel();
+    let mut resumable = None;
+    let handle = match args.dimension {
+        Dimension::Nether => {
+            eprintln!(
+                "Cracking with {} blocks on {} threads ({}, {})",
+                blocks.len(),
+                args.threads,
+                args.mode,
+                args.output
+            );
+            if args.range == SEARCH_SPACE && args.coordinator.is_none() {
+                let estimate = estimate_crack(&blocks, args.mode, args.profile, args.output, args.tolerance, args.threads, &calibration);
+                eprintln!("Expecting about {:.0} results in {:.0}s", estimate.results, estimate.seconds);
+            }
+            if args.output == OutputMode::BedrockSeed {
+                let surface = if reports_floor_seeds(&blocks, args.mode, args.profile) { "floor" } else { "roof" };
+                eprintln!("Reporting the bedrock seeds of the {surface}");
+            }
+            match (&args.coordinator, &args.checkpoint) {
+                (Some(address), _) => {
+                    let listener = match TcpListener::bind(address) {
+                        Ok(listener) => listener,
+                        Err(err) => {
+                            eprintln!("Couldnt listen on {address}: {err}");
+                            exit(1);
+                        }
+                    };
+                    eprintln!("Waiting for workers on {address}");
+                    match run_coordinator(listener, &blocks, args.mode, args.profile, args.output, args.tolerance, &args.token, sender) {
+                        Ok(handle) => handle,
+                        Err(err) => {
+                            eprintln!("Couldnt start coordinator: {err}");
+                            exit(1);
+                        }
+                    }
+                }
+                (None, Some(path)) => {
+                    let checkpoint = Checkpoint::open(path, &blocks, args.mode, args.profile, args.output, args.tolerance, args.range.clone());
+                    let checkpoint = match checkpoint {
+                        Ok(checkpoint) => checkpoint,
+                        Err(err) => {
+                            eprintln!("Couldnt open checkpoint: {err}");
+                            exit(1);
+                        }
+                    };
+                    if checkpoint.finished_chunks() != 0 {
+                        eprintln!("Resuming from {path}");
+                    }
+                    let handle = resume_bedrock_search(&blocks, args.threads, args.mode, args.profile, args.output, args.tolerance, &checkpoint, sender);
+                    resumable = Some(checkpoint);
+                    handle
+                }
+                (None, None) => search_bedrock_range(&blocks, args.threads, args.mode, args.profile, args.output, args.tolerance, args.range.clone(), sender),
+            }
+        }
+        Dimension::Overworld => {
+            eprintln!(
+                "Cracking overworld with {} blocks on {} threads",
+                blocks.len(),
+                args.threads
+            );
+            if args.structure_seed.is_none() {
+                eprintln!("Without a structure seed all 2^64 world seeds have to be searched, this takes years");
+            }
+            search_overworld_pattern(&blocks, args.threads, args.structure_seed, args.tolerance, sender)
+        }
+    };
+
+    let total = match args.dimension {
+        Dimension::Nether if args.coordinator.is_some() => SEARCH_SPACE.end,
+        Dimension::Nether => args.range.end - args.range.start,
+        Dimension::Overworld => SEARCH_SPACE.end,
+    };
+    let mut progress = 0;
+    let mut results = 0u64;
+    while let Ok(event) = receiver.recv() {
+        let line = match event {
+            CrackProgress::Seed(seed) => format!("{}", seed as i64),
+            CrackProgress::PartialMatch(seed, mismatches) => {
+                format!("{} ({} mismatches)", seed as i64, mismatches)
+            }
+            CrackProgress::Progress(num) => {
+                progress += num;
+                eprint!("\r{:.2}%", progress as f64 * 100.0 / total as f64);
+                continue;
+            }
+            CrackProgress::ChunkDone(chunk) => {
+                // the chunk only counts as searched once its seeds are written, otherwise
+                // stopping the crack now would skip them after a resume
+                if let Err(err) = writer.flush() {
+                    eprintln!("Couldnt write seeds: {err}");
+                    exit(1);
+                }
+                if let Some(checkpoint) = &mut resumable {
+                    if let Err(err) = checkpoint.finish(chunk) {
+                        eprintln!("\rCouldnt save the checkpoint: {err}");
+                    }
+                }
+                continue;
+            }
+            CrackProgress::Finished | CrackProgress::Cancelled => break,
+        };
+        results += 1;
+        if let Err(err) = writeln!(writer, "{line}") {
+            eprintln!("Couldnt write seed: {err}");
+            exit(1);
+        }
+    }
+    if let Err(err) = writer.flush() {
+        eprintln!("Couldnt write seeds: {err}");
+        exit(1);
+    }
+    match handle.join() {
+        CrackOutcome::Finished => eprintln!("\rFound {results} results"),
+        CrackOutcome::Cancelled => eprintln!("\rCancelled after finding {results} results"),
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut block_file = None;
+    let mut threads = None;
+    let mut dimension = Dimension::Nether;
+    let mut mode = None;
+    let mut output = None;
+    let mut structure_seed = None;
+    let mut range = SEARCH_SPACE;
+    let mut tolerance = 0;
+    let mut seed_file = None;
+    let mut checkpoint = None;
+    let mut coordinator = None;
+    let mut worker = None;
+    let mut token = String::new();
+    let mut world = None;
+    let mut area = [0, 0, 3, 3];
+    let mut schematic = None;
+    let mut origin = None;
+    let mut profile = BedrockProfile::NETHER;
+    let mut benchmark = false;
+    let mut calibration = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(format!("Missing value for {name}"));
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                exit(0);
+            }
+            "-t" | "--threads" => {
+                let value = value(&arg)?;
+                match value.parse::<u64>() {
+                    Ok(x) if x != 0 => threads = Some(x),
+                    _ => return Err(format!("Invalid thread count: {value}")),
+                }
+            }
+            "-d" | "--dimension" => dimension = value(&arg)?.parse()?,
+            "-m" | "--mode" => mode = Some(value(&arg)?.parse()?),
+            "-o" | "--output" => output = Some(value(&arg)?.parse()?),
+            "-s" | "--structure-seed" => {
+                let value = value(&arg)?;
+                let seed = value
+                    .parse::<i64>()
+                    .map_err(|_| format!("Invalid structure seed: {value}"))?;
+                structure_seed = Some(seed as u64);
+            }
+            "-k" | "--tolerance" => {
+                let value = value(&arg)?;
+                tolerance = value
+                    .parse()
+                    .map_err(|_| format!("Invalid tolerance: {value}"))?;
+            }
+            "--shard" => {
+                let value = value(&arg)?;
+                let shard = value
+                    .split_once('/')
+                    .and_then(|(shard, count)| Some((shard.parse::<u64>().ok()?, count.parse::<u64>().ok()?)));
+                match shard {
+                    Some((shard, count)) if shard < count => range = shard_range(shard, count),
+                    _ => return Err(format!("Invalid shard: {value}, expected e.g. 0/4")),
+                }
+            }
+            "-f" | "--file" => seed_file = Some(value(&arg)?),
+            "-c" | "--checkpoint" => checkpoint = Some(value(&arg)?),
+            "--coordinator" => coordinator = Some(value(&arg)?),
+            "--worker" => worker = Some(value(&arg)?),
+            "--token" => {
+                token = value(&arg)?;
+                if token.contains(char::is_whitespace) {
+                    return Err("The token cant contain spaces".to_string());
+                }
+            }
+            "-w" | "--world" => world = Some(value(&arg)?),
+            "--schematic" => schematic = Some(value(&arg)?),
+            "--origin" => {
+                let value = value(&arg)?;
+                let coords: Vec<_> = value.split(',').map(|coord| coord.trim().parse::<i32>()).collect();
+                match coords[..] {
+                    [Ok(x), Ok(y), Ok(z)] => origin = Some([x, y, z]),
+                    _ => return Err(format!("Invalid origin: {value}, expected e.g. 100,1,-20")),
+                }
+            }
+            "--area" => {
+                let value = value(&arg)?;
+                let coords: Vec<_> = value.split(',').map(|coord| coord.trim().parse::<i32>()).collect();
+                match coords[..] {
+                    [Ok(x1), Ok(z1), Ok(x2), Ok(z2)] => area = [x1.min(x2), z1.min(z2), x1.max(x2), z1.max(z2)],
+                    _ => return Err(format!("Invalid area: {value}, expected e.g. 0,0,3,3")),
+                }
+            }
+            "--floor-y" | "--roof-y" | "--gradient-depth" => {
+                let value = value(&arg)?;
+                let number = value.parse::<i32>().map_err(|_| format!("Invalid {arg}: {value}"))?;
+                match arg.as_str() {
+                    "--floor-y" => profile.floor_y = number,
+                    "--roof-y" => profile.roof_y = number,
+                    _ => profile.depth = number,
+                }
+            }
+            "--floor-random" => profile.floor_hash = random_hash(&value(&arg)?),
+            "--roof-random" => profile.roof_hash = random_hash(&value(&arg)?),
+            "--benchmark" => benchmark = true,
+            "--calibration" => calibration = Some(value(&arg)?),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option: {arg}")),
+            _ if block_file.is_none() => block_file = Some(arg),
+            _ => return Err(format!("Unexpected argument: {arg}")),
+        }
+    }
+
+    let threads = threads.unwrap_or_else(|| {
+        thread::available_parallelism()
+            .map(|threads| threads.get() as u64)
+            .unwrap_or(1)
+    });
+
+    if coordinator.is_some() && (dimension != Dimension::Nether || checkpoint.is_some() || range != SEARCH_SPACE) {
+        return Err("--coordinator only works for full nether searches without a checkpoint".to_string());
+    }
+    // the overworld always searches world seeds with the vanilla random
+    if dimension == Dimension::Overworld && (mode.is_some() || output.is_some()) {
+        return Err("--mode and --output only apply to the nether, the overworld always reports world seeds".to_string());
+    }
+    if dimension == Dimension::Nether && structure_seed.is_some() {
+        return Err("--structure-seed only applies to the overworld".to_string());
+    }
+    let mode = mode.unwrap_or(CrackerMode::Normal);
+    let output = output.unwrap_or(OutputMode::WorldSeed);
+    if (world.is_some() || schematic.is_some()) && dimension != Dimension::Nether {
+        return Err("--world and --schematic only read the nether".to_string());
+    }
+    let schematic = match (schematic, origin) {
+        (Some(schematic), Some(origin)) => Some((schematic, origin)),
+        (Some(_), None) => return Err("--schematic needs an --origin".to_string()),
+        (None, _) => None,
+    };
+    if !profile.is_valid() {
+        return Err("The bedrock gradients overlap or are empty".to_string());
+    }
+    if !benchmark && worker.is_none() && world.is_none() && schematic.is_none() && block_file.is_none() {
+        return Err("Missing block file".to_string());
+    }
+
+    Ok(Args {
+        block_file,
+        threads,
+        dimension,
+        mode,
+        output,
+        structure_seed,
+        range,
+        tolerance,
+        seed_file,
+        checkpoint,
+        coordinator,
+        worker,
+        token,
+        world,
+        area,
+        schematic,
+        profile,
+        benchmark,
+        calibration,
+    })
+}
@@ -0,0 +1,387 @@
+mod xoroshiro;
+
+use std::cmp::min;
+
+use crate::{CrackProgress, MASK48};
+use crate::block_data::BlockFilter;
+use crate::handle::{CrackHandle, SearchState};
+use crate::overworld::xoroshiro::{positional_long, Xoroshiro};
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::sender::Sender;
+
+//md5 hashes of minecraft:bedrock_floor and minecraft:deepslate
+const BEDROCK_FLOOR_MD5: (u64, u64) = (0xBBF7_928B_7BF1_D285, 0xC4DC_7CF9_0E1B_3B94);
+const DEEPSLATE_MD5: (u64, u64) = (0x907D_DBC8_BAE9_5DCF, 0xCFAD_A31A_09F2_6A63);
+
+pub const BEDROCK_LAYERS: [i32; 5] = [-64, -63, -62, -61, -60];
+pub const DEEPSLATE_LAYERS: [i32; 9] = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+
+const CHUNK_SIZE: u64 = 1 << 22;
+
+/// The two vertical gradients of the overworld
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Gradient {
+    Bedrock,
+    Deepslate,
+}
+
+impl Gradient {
+    fn of(y: i32) -> Option<Gradient> {
+        if BEDROCK_LAYERS.contains(&y) {
+            Some(Gradient::Bedrock)
+        } else if DEEPSLATE_LAYERS.contains(&y) {
+            Some(Gradient::Deepslate)
+        } else {
+            None
+        }
+    }
+
+    /// the block that gets placed if the gradient passes
+    fn block_type(&self) -> BlockType {
+        match self {
+            Gradient::Bedrock => BlockType::BEDROCK,
+            Gradient::Deepslate => BlockType::DEEPSLATE,
+        }
+    }
+
+    /// chance that the gradient passes, outside of (0, 1) no random is used
+    fn chance(&self, y: i32) -> f64 {
+        match self {
+            Gradient::Bedrock => (-59 - y) as f64 / 5.0,
+            Gradient::Deepslate => (8 - y) as f64 / 8.0,
+        }
+    }
+
+    fn factory(&self, world_factory: (u64, u64)) -> (u64, u64) {
+        let (lo, hi) = match self {
+            Gradient::Bedrock => BEDROCK_FLOOR_MD5,
+            Gradient::Deepslate => DEEPSLATE_MD5,
+        };
+        Xoroshiro::new(lo ^ world_factory.0, hi ^ world_factory.1).fork_positional()
+    }
+}
+
+fn world_factory(world_seed: u64) -> (u64, u64) {
+    Xoroshiro::with_seed(world_seed).fork_positional()
+}
+
+/// Mth.getSeed, the xoroshiro random uses all 64 bits so the shift has to keep the sign
+fn pos_seed(x: i32, y: i32, z: i32) -> u64 {
+    ((BlockFilter::hashcode(x, y, z) << 16) as i64 >> 16) as u64
+}
+
+/// Places overworld bedrock and deepslate the same way the game does for a world seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverworldGenerator {
+    bedrock: (u64, u64),
+    deepslate: (u64, u64),
+}
+
+impl OverworldGenerator {
+    /// The overworld uses every bit of the world seed
+    pub fn new(world_seed: u64) -> Self {
+        let world_factory = world_factory(world_seed);
+        Self {
+            bedrock: Gradient::Bedrock.factory(world_factory),
+            deepslate: Gradient::Deepslate.factory(world_factory),
+        }
+    }
+
+    /// None if y is not part of the bedrock floor or the deepslate transition.
+    /// Deepslate is only placed in place of stone, caves and ores stay untouched
+    pub fn block_at(&self, x: i32, y: i32, z: i32) -> Option<BlockType> {
+        let gradient = Gradient::of(y)?;
+        let factory = match gradient {
+            Gradient::Bedrock => self.bedrock,
+            Gradient::Deepslate => self.deepslate,
+        };
+        let chance = gradient.chance(y);
+        let passes = if chance >= 1.0 {
+            true
+        } else if chance <= 0.0 {
+            false
+        } else {
+            let mut random = Xoroshiro::new(pos_seed(x, y, z) ^ factory.0, factory.1);
+            (random.next_float() as f64) < chance
+        };
+
+        Some(if passes { gradient.block_type() } else { BlockType::OTHER })
+    }
+
+    /// All bedrock floor and deepslate transition blocks between the two corners (inclusive)
+    pub fn generate_area(&self, x1: i32, z1: i32, x2: i32, z2: i32) -> Vec<Block> {
+        let mut blocks = vec![];
+        for y in BEDROCK_LAYERS.into_iter().chain(DEEPSLATE_LAYERS) {
+            for z in z1.min(z2)..=z1.max(z2) {
+                for x in x1.min(x2)..=x1.max(x2) {
+                    if let Some(block_type) = self.block_at(x, y, z) {
+                        blocks.push(Block::new(x, y, z, block_type));
+                    }
+                }
+            }
+        }
+        blocks
+    }
+}
+
+#[derive(Debug, Clone)]
+struct OverworldCheck {
+    pos_seed: u64,
+    // nextFloat() < chance expressed with the upper 24 bits of nextLong()
+    threshold: u64,
+    passes: bool,
+    chance: f64,
+}
+
+impl OverworldCheck {
+    /// None if the block doesnt contain information
+    fn new(block: &Block) -> Option<(Gradient, OverworldCheck)> {
+        let gradient = Gradient::of(block.y)?;
+        let chance = gradient.chance(block.y);
+        if chance <= 0.0 || chance >= 1.0 {
+            return None;
+        }
+        let passes = match (gradient, block.block_type) {
+            (Gradient::Bedrock, BlockType::BEDROCK) => true,
+            (Gradient::Bedrock, _) => false,
+            (Gradient::Deepslate, BlockType::DEEPSLATE) => true,
+            (Gradient::Deepslate, BlockType::OTHER) => false,
+            (Gradient::Deepslate, BlockType::BEDROCK) => return None,
+        };
+        let check = Self {
+            pos_seed: pos_seed(block.x, block.y, block.z),
+            threshold: (chance * (1 << 24) as f64).ceil() as u64,
+            passes,
+            chance: if passes { chance } else { 1.0 - chance },
+        };
+        Some((gradient, check))
+    }
+
+    /// returns true if the block contradicts the seed
+    #[inline(always)]
+    fn check(&self, factory: (u64, u64)) -> bool {
+        ((positional_long(self.pos_seed, factory) >> 40) < self.threshold) != self.passes
+    }
+}
+
+/// The checks for the overworld, the filter tree doesnt work because every bit of the seed
+/// influences the result
+#[derive(Debug, Clone)]
+pub struct OverworldChecks {
+    gradients: Vec<(Gradient, Vec<OverworldCheck>)>,
+    tolerance: u32,
+}
+
+impl OverworldChecks {
+    pub fn new(blocks: &[Block], tolerance: u32) -> Self {
+        let mut gradients: Vec<(Gradient, Vec<OverworldCheck>)> = vec![];
+        for (gradient, check) in blocks.iter().filter_map(OverworldCheck::new) {
+            match gradients.iter_mut().find(|(other, _)| *other == gradient) {
+                Some((_, checks)) => checks.push(check),
+                None => gradients.push((gradient, vec![check])),
+            }
+        }
+
+        //most information first
+        for (_, checks) in gradients.iter_mut() {
+            checks.sort_by(|a, b| a.chance.partial_cmp(&b.chance).unwrap());
+        }
+        gradients.sort_by(|(_, a), (_, b)| {
+            let a: f64 = a.iter().map(|check| check.chance).product();
+            let b: f64 = b.iter().map(|check| check.chance).product();
+            a.partial_cmp(&b).unwrap()
+        });
+
+        Self {
+            gradients,
+            tolerance,
+        }
+    }
+
+    /// returns the amount of mismatches or None if there are more than allowed
+    pub fn check(&self, world_seed: u64) -> Option<u32> {
+        let world_factory = world_factory(world_seed);
+        let mut mismatches = 0;
+        for (gradient, checks) in self.gradients.iter() {
+            let factory = gradient.factory(world_factory);
+            for check in checks.iter() {
+                if check.check(factory) {
+                    mismatches += 1;
+                    if mismatches > self.tolerance {
+                        return None;
+                    }
+                }
+            }
+        }
+        Some(mismatches)
+    }
+
+    fn success_chance(&self) -> f64 {
+        self.gradients
+            .iter()
+            .flat_map(|(_, checks)| checks)
+            .map(|check| check.chance)
+            .product()
+    }
+}
+
+/// whether a block can generate in the overworld, bedrock never generates on the deepslate layers
+pub fn is_possible(block: &Block) -> bool {
+    !(block.block_type == BlockType::BEDROCK && DEEPSLATE_LAYERS.contains(&block.y))
+}
+
+/// this estimate is naive
+pub fn estimate_result_amount(blocks: &[Block]) -> u64 {
+    let resulting_seeds = OverworldChecks::new(blocks, 0).success_chance() * 2f64.powi(64);
+    resulting_seeds as u64 // saturates
+}
+
+/// Every bit of the world seed has to be brute forced. Without a structure seed that is 2^64 seeds,
+/// which takes years even on fast machines.
+/// With a structure seed (e.g. from the nether) only the upper 16 bits are searched.
+/// Progress is reported relative to 2^48 like the nether search.
+/// The results are always world seeds, blocks that arent possible (see is_possible) are ignored
+pub fn search_overworld_pattern<S: Sender + 'static>(
+    blocks: &[Block],
+    thread_count: u64,
+    structure_seed: Option<u64>,
+    tolerance: u32,
+    sender: S,
+) -> CrackHandle {
+    let checks = OverworldChecks::new(blocks, tolerance);
+    let (lower_start, lower_end, space_bits) = match structure_seed {
+        Some(seed) => (seed & MASK48, (seed & MASK48) + 1, 16),
+        None => (0, 1 << 48, 64),
+    };
+
+    let jobs = (0..thread_count).map(|thread| {
+        let start_bits = (thread * (1 << 16)) / thread_count;
+        let end_bits = ((thread + 1) * (1 << 16)) / thread_count;
+
+        let checks = checks.clone();
+        let sender = sender.clone();
+
+        move |state: &SearchState| {
+            let mut checked = 0u64;
+            for upper_bits in start_bits..end_bits {
+                let mut lower_bits = lower_start;
+                while lower_bits < lower_end {
+                    if state.should_stop() {
+                        return;
+                    }
+                    let chunk_end = min(lower_bits + CHUNK_SIZE, lower_end);
+                    for lower_bits in lower_bits..chunk_end {
+                        let seed = upper_bits << 48 | lower_bits;
+                        if let Some(mismatches) = checks.check(seed) {
+                            sender.send(CrackProgress::result(seed, mismatches));
+                        }
+                    }
+                    checked += chunk_end - lower_bits;
+                    lower_bits = chunk_end;
+
+                    if checked >= CHUNK_SIZE || upper_bits + 1 == end_bits && lower_bits == lower_end {
+                        let progress = ((checked as u128) << 48 >> space_bits) as u64;
+                        checked = 0;
+                        //dropping the receiver stops the threads
+                        if !sender.send(CrackProgress::Progress(progress)) {
+                            state.cancel();
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    });
+    CrackHandle::spawn(jobs.collect(), sender)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    #[test]
+    fn test_pos_seed() {
+        assert_eq!(pos_seed(-98, 4, -469), 99261249361405);
+        assert_eq!(pos_seed(1, 2, 3) as i64, -33674130277896);
+    }
+
+    #[test]
+    fn test_fixed_layers() {
+        let generator = OverworldGenerator::new(WORLD_SEED);
+        let blocks = generator.generate_area(0, 0, 15, 15);
+        assert_eq!(blocks.len(), 16 * 16 * 14);
+        for block in blocks {
+            match block.y {
+                -64 => assert_eq!(block.block_type, BlockType::BEDROCK),
+                0 => assert_eq!(block.block_type, BlockType::DEEPSLATE),
+                8 => assert_eq!(block.block_type, BlockType::OTHER),
+                _ => {}
+            }
+        }
+    }
+
+    // the floor and the transition of the world seed for 0..8 x 0..4, one group per z.
+    // Computed outside of this crate with the Xoroshiro128PlusPlus and MD5 of the JDK,
+    // following RandomState.getOrCreateRandomFactory and VerticalGradientConditionSource
+    const VANILLA_LAYERS: [(i32, &str); 11] = [
+        (-63, "#.#.#### ######## ######## .#.#####"),
+        (-62, "#.#.#.#. ####..#. ##.##### .#.####."),
+        (-61, "..#.#... #.....#. ...##.## .#.##..."),
+        (-60, "..#..... #.....#. ...#...# ........"),
+        (1, ".####### ##.#.##. ######## ####.###"),
+        (2, ".####.## ##.#.... ##..#.## ####.###"),
+        (3, "..###.## ##...... #...#.## ####.###"),
+        (4, "..#.#.## ##...... ....#.## ####.##."),
+        (5, "..#.#.## ##...... ....#.#. ####.###"),
+        (6, "..#...#. .#...... ....#... .#...#.."),
+        (7, "......#. ........ ....#... ........"),
+    ];
+
+    #[test]
+    fn test_vanilla_layers() {
+        let generator = OverworldGenerator::new(WORLD_SEED);
+        for (y, layer) in VANILLA_LAYERS {
+            let gradient = Gradient::of(y).unwrap();
+            for (z, row) in layer.split(' ').enumerate() {
+                for (x, block) in row.chars().enumerate() {
+                    let expected = if block == '#' { gradient.block_type() } else { BlockType::OTHER };
+                    assert_eq!(generator.block_at(x as i32, y, z as i32), Some(expected), "{x} {y} {z}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_blocks_match() {
+        let blocks = OverworldGenerator::new(WORLD_SEED).generate_area(0, 0, 7, 7);
+        let checks = OverworldChecks::new(&blocks, 0);
+        assert_eq!(checks.check(WORLD_SEED), Some(0));
+        assert_eq!(checks.check(WORLD_SEED ^ 1 << 60), None);
+    }
+
+    #[test]
+    fn test_search_structure_seed() {
+        let blocks = OverworldGenerator::new(WORLD_SEED).generate_area(0, 0, 3, 3);
+        let (sender, receiver) = mpsc::channel();
+
+        search_overworld_pattern(&blocks, 2, Some(WORLD_SEED & MASK48), 0, sender);
+
+        let mut seeds = vec![];
+        let mut progress = 0;
+        for event in receiver {
+            match event {
+                CrackProgress::Seed(seed) => seeds.push(seed),
+                CrackProgress::Progress(num) => progress += num,
+                CrackProgress::PartialMatch(_, _) => panic!("No tolerance was given"),
+                CrackProgress::ChunkDone(_) => panic!("No checkpoint was given"),
+                CrackProgress::Finished => {}
+                CrackProgress::Cancelled => panic!("Nothing cancelled the search"),
+            }
+        }
+        assert_eq!(seeds, vec![WORLD_SEED]);
+        assert_eq!(progress, 1 << 48);
+    }
+}
@@ -0,0 +1,80 @@
+const SILVER_RATIO_64: u64 = 0x6A09_E667_F3BC_C909;
+const GOLDEN_RATIO_64: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// Xoroshiro128++ as used by the random sources of 1.18+ world generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Xoroshiro {
+    lo: u64,
+    hi: u64,
+}
+
+impl Xoroshiro {
+    /// same as XoroshiroRandomSource(long seed)
+    pub fn with_seed(seed: u64) -> Self {
+        let lo = seed ^ SILVER_RATIO_64;
+        let hi = lo.wrapping_add(GOLDEN_RATIO_64);
+        Self::new(mix_stafford13(lo), mix_stafford13(hi))
+    }
+
+    /// same as XoroshiroRandomSource(long lo, long hi)
+    pub fn new(lo: u64, hi: u64) -> Self {
+        if lo | hi == 0 {
+            return Self {
+                lo: GOLDEN_RATIO_64,
+                hi: SILVER_RATIO_64,
+            };
+        }
+        Self { lo, hi }
+    }
+
+    #[inline(always)]
+    pub fn next_long(&mut self) -> u64 {
+        let (lo, mut hi) = (self.lo, self.hi);
+        let result = lo.wrapping_add(hi).rotate_left(17).wrapping_add(lo);
+        hi ^= lo;
+        self.lo = lo.rotate_left(49) ^ hi ^ (hi << 21);
+        self.hi = hi.rotate_left(28);
+        result
+    }
+
+    pub fn next_float(&mut self) -> f32 {
+        (self.next_long() >> 40) as f32 * 5.960_464_5E-8
+    }
+
+    /// the seeds of the PositionalRandomFactory created by forkPositional
+    pub fn fork_positional(&mut self) -> (u64, u64) {
+        (self.next_long(), self.next_long())
+    }
+}
+
+/// the first output of a positional random at a position
+/// takes the result of Mth.getSeed and the seeds of the positional factory
+#[inline(always)]
+pub fn positional_long(pos_seed: u64, (lo, hi): (u64, u64)) -> u64 {
+    let lo = pos_seed ^ lo;
+    lo.wrapping_add(hi).rotate_left(17).wrapping_add(lo)
+}
+
+fn mix_stafford13(mut seed: u64) -> u64 {
+    seed = (seed ^ (seed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    seed = (seed ^ (seed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    seed ^ (seed >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mix_stafford13() {
+        // first output of splitmix64 seeded with 0
+        assert_eq!(mix_stafford13(GOLDEN_RATIO_64), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn test_positional_long() {
+        let factory = (123456789, 987654321);
+        let mut random = Xoroshiro::new(42 ^ factory.0, factory.1);
+        assert_eq!(positional_long(42, factory), random.next_long());
+    }
+}
@@ -3,6 +3,7 @@ use std::fmt::Formatter;
 use crate::raw_data::block_type::BlockType;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Block {
     pub x: i32,
     pub y: i32,
@@ -29,4 +30,4 @@ impl fmt::Display for Block {
             self.x, self.y, self.z, self.block_type
         )
     }
-}
\ No newline at end of file
+}
@@ -1,14 +1,19 @@
 use std::fmt;
 use std::fmt::Formatter;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlockType {
     BEDROCK,
     OTHER,
+    /// only used in the overworld transition layers
+    DEEPSLATE,
 }
 
 impl BlockType {
-    pub const ALL: [BlockType; 2] = [BlockType::BEDROCK, BlockType::OTHER];
+    pub const ALL: [BlockType; 3] = [BlockType::BEDROCK, BlockType::OTHER, BlockType::DEEPSLATE];
+    pub const NETHER: [BlockType; 2] = [BlockType::BEDROCK, BlockType::OTHER];
 }
 
 impl fmt::Display for BlockType {
@@ -19,7 +24,19 @@ impl fmt::Display for BlockType {
             match self {
                 BlockType::BEDROCK => "Bedrock",
                 BlockType::OTHER => "Other",
+                BlockType::DEEPSLATE => "Deepslate",
             }
         )
     }
 }
+
+impl FromStr for BlockType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BlockType::ALL
+            .into_iter()
+            .find(|block_type| s.eq_ignore_ascii_case(&block_type.to_string()))
+            .ok_or(format!("Unknown block type: {s}"))
+    }
+}
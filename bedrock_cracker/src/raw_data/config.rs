@@ -0,0 +1,174 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Formatter;
+use crate::overworld;
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::modes::Dimension;
+use crate::raw_data::profile::BedrockProfile;
+
+/// Why a line of a block config was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// a coordinate that isnt an integer
+    BadNumber(String),
+    MissingCoordinate,
+    /// y isnt on one of the layers the cracker knows
+    BadLayer(i32),
+    UnknownType(String),
+    /// a block that cant exist there on any seed, like other blocks on y=0
+    Impossible(Block),
+    /// anything after the block type
+    TrailingInput(String),
+    /// the position was already used on an earlier line
+    Duplicate { first_line: usize },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::BadNumber(text) => write!(f, "\"{text}\" is not a number"),
+            ConfigError::MissingCoordinate => write!(f, "expected \"x y z Bedrock|Other\""),
+            ConfigError::BadLayer(y) => write!(f, "y={y} is not a bedrock layer"),
+            ConfigError::UnknownType(name) => write!(f, "unknown block type \"{name}\""),
+            ConfigError::Impossible(block) if block.block_type == BlockType::BEDROCK => write!(f, "Bedrock at y={} is impossible, that layer never has bedrock", block.y),
+            ConfigError::Impossible(block) => write!(f, "{} at y={} is impossible, that layer is always bedrock", block.block_type, block.y),
+            ConfigError::TrailingInput(text) => write!(f, "unexpected \"{text}\" after the block type"),
+            ConfigError::Duplicate { first_line } => write!(f, "same position as line {first_line}"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+/// A rejected line, lines are counted from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineError {
+    pub line: usize,
+    pub error: ConfigError,
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl Error for LineError {}
+
+/// the layers the cracker can use blocks from, the solid layers are only checked for consistency.
+/// The profile only applies to the nether
+pub fn is_valid_y(y: i32, dimension: Dimension, profile: BedrockProfile) -> bool {
+    match dimension {
+        Dimension::Nether => profile.floor_layers().contains(&y) || profile.roof_layers().contains(&y),
+        Dimension::Overworld => (-63..=-60).contains(&y) || (1..=7).contains(&y),
+    }
+}
+
+/// Parses a single "x y z Bedrock|Other" line, a missing block type means bedrock
+pub fn parse_line(line: &str, dimension: Dimension, profile: BedrockProfile) -> Result<Block, ConfigError> {
+    let mut components = line.split_whitespace();
+    let mut coord = || {
+        let text = components.next().ok_or(ConfigError::MissingCoordinate)?;
+        text.parse::<i32>().map_err(|_| ConfigError::BadNumber(text.to_string()))
+    };
+    let (x, y, z) = (coord()?, coord()?, coord()?);
+    if !is_valid_y(y, dimension, profile) {
+        return Err(ConfigError::BadLayer(y));
+    }
+
+    let block_types = match dimension {
+        Dimension::Nether => &BlockType::NETHER[..],
+        Dimension::Overworld => &BlockType::ALL[..],
+    };
+    let block_type = match components.next() {
+        Some(name) => block_types
+            .iter()
+            .copied()
+            .find(|block_type| name.eq_ignore_ascii_case(&block_type.to_string()))
+            .ok_or_else(|| ConfigError::UnknownType(name.to_string()))?,
+        None => BlockType::BEDROCK,
+    };
+    let rest: Vec<_> = components.collect();
+    if !rest.is_empty() {
+        return Err(ConfigError::TrailingInput(rest.join(" ")));
+    }
+    let block = Block::new(x, y, z, block_type);
+    if dimension == Dimension::Nether && profile.is_solid(y) && block_type != BlockType::BEDROCK {
+        return Err(ConfigError::Impossible(block));
+    }
+    if dimension == Dimension::Overworld && !overworld::is_possible(&block) {
+        return Err(ConfigError::Impossible(block));
+    }
+    Ok(block)
+}
+
+/// Parses a whole config, one block per line. Blank lines are skipped.
+/// Returns the blocks of every good line and an error for every other line
+pub fn parse_config(config: &str, dimension: Dimension, profile: BedrockProfile) -> (Vec<Block>, Vec<LineError>) {
+    let mut blocks = vec![];
+    let mut errors = vec![];
+    let mut positions = HashMap::new();
+    for (index, line) in config.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let result = parse_line(line, dimension, profile).and_then(|block| match positions.entry((block.x, block.y, block.z)) {
+            Entry::Occupied(entry) => Err(ConfigError::Duplicate { first_line: *entry.get() }),
+            Entry::Vacant(entry) => {
+                entry.insert(line_number);
+                Ok(block)
+            }
+        });
+        match result {
+            Ok(block) => blocks.push(block),
+            Err(error) => errors.push(LineError { line: line_number, error }),
+        }
+    }
+    (blocks, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = "1 2 3 Bedrock\n\n4 123 -6 other\n7 8 9\na 1 1\n1 2 3 Other\n5 1 5 Stone\n5 1 5 Bedrock x\n6 1\n";
+        let (blocks, errors) = parse_config(config, Dimension::Nether, BedrockProfile::NETHER);
+        assert_eq!(blocks, vec![
+            Block::new(1, 2, 3, BlockType::BEDROCK),
+            Block::new(4, 123, -6, BlockType::OTHER),
+        ]);
+        let errors: Vec<_> = errors.into_iter().map(|error| (error.line, error.error)).collect();
+        assert_eq!(errors, vec![
+            (4, ConfigError::BadLayer(8)),
+            (5, ConfigError::BadNumber("a".to_string())),
+            (6, ConfigError::Duplicate { first_line: 1 }),
+            (7, ConfigError::UnknownType("Stone".to_string())),
+            (8, ConfigError::TrailingInput("x".to_string())),
+            (9, ConfigError::MissingCoordinate),
+        ]);
+    }
+
+    #[test]
+    fn test_dimension() {
+        assert_eq!(parse_line("0 -62 0", Dimension::Overworld, BedrockProfile::NETHER), Ok(Block::new(0, -62, 0, BlockType::BEDROCK)));
+        assert_eq!(parse_line("0 5 0 deepslate", Dimension::Overworld, BedrockProfile::NETHER), Ok(Block::new(0, 5, 0, BlockType::DEEPSLATE)));
+        assert_eq!(parse_line("0 4 0 Deepslate", Dimension::Nether, BedrockProfile::NETHER), Err(ConfigError::UnknownType("Deepslate".to_string())));
+        assert_eq!(parse_line("0 -62 0", Dimension::Nether, BedrockProfile::NETHER), Err(ConfigError::BadLayer(-62)));
+        // the deepslate layers have no bedrock the cracker could use
+        assert_eq!(parse_line("0 5 0 Bedrock", Dimension::Overworld, BedrockProfile::NETHER), Err(ConfigError::Impossible(Block::new(0, 5, 0, BlockType::BEDROCK))));
+    }
+
+    #[test]
+    fn test_solid_layers() {
+        assert_eq!(parse_line("5 0 5 Bedrock", Dimension::Nether, BedrockProfile::NETHER), Ok(Block::new(5, 0, 5, BlockType::BEDROCK)));
+        assert_eq!(parse_line("5 127 5", Dimension::Nether, BedrockProfile::NETHER), Ok(Block::new(5, 127, 5, BlockType::BEDROCK)));
+        assert_eq!(parse_line("5 0 5 Other", Dimension::Nether, BedrockProfile::NETHER), Err(ConfigError::Impossible(Block::new(5, 0, 5, BlockType::OTHER))));
+        assert_eq!(parse_line("5 128 5", Dimension::Nether, BedrockProfile::NETHER), Err(ConfigError::BadLayer(128)));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod block_type;
 pub mod modes;
+pub mod profile;
 pub mod block;
+pub mod config;
 pub mod sender;
\ No newline at end of file
@@ -1,7 +1,9 @@
 use std::fmt;
 use std::fmt::Formatter;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CrackerMode {
     #[default]
     Normal,
@@ -25,16 +27,32 @@ impl fmt::Display for CrackerMode {
     }
 }
 
+impl FromStr for CrackerMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "normal" | "vanilla" => Ok(CrackerMode::Normal),
+            "paper1_18" | "paper" => Ok(CrackerMode::Paper1_18),
+            _ => Err(format!("Unknown cracker mode: {s}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OutputMode {
     #[default]
     WorldSeed,
     StructureSeed,
+    /// the seed of the positional random of the surface with the most information,
+    /// stops before the structure seed so one surface can be cracked on its own
+    BedrockSeed,
 }
 
 
 impl OutputMode {
-    pub const ALL: [OutputMode; 2] = [OutputMode::WorldSeed, OutputMode::StructureSeed];
+    pub const ALL: [OutputMode; 3] = [OutputMode::WorldSeed, OutputMode::StructureSeed, OutputMode::BedrockSeed];
 }
 
 impl fmt::Display for OutputMode {
@@ -45,7 +63,58 @@ impl fmt::Display for OutputMode {
             match self {
                 OutputMode::WorldSeed => "World Seeds",
                 OutputMode::StructureSeed => "Structure Seeds",
+                OutputMode::BedrockSeed => "Bedrock Seeds",
+            }
+        )
+    }
+}
+
+impl FromStr for OutputMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "world" | "worldseed" => Ok(OutputMode::WorldSeed),
+            "structure" | "structureseed" => Ok(OutputMode::StructureSeed),
+            "bedrock" | "bedrockseed" => Ok(OutputMode::BedrockSeed),
+            _ => Err(format!("Unknown output mode: {s}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dimension {
+    #[default]
+    Nether,
+    Overworld,
+}
+
+impl Dimension {
+    pub const ALL: [Dimension; 2] = [Dimension::Nether, Dimension::Overworld];
+}
+
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Dimension::Nether => "Nether",
+                Dimension::Overworld => "Overworld",
             }
         )
     }
 }
+
+impl FromStr for Dimension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "nether" => Ok(Dimension::Nether),
+            "overworld" => Ok(Dimension::Overworld),
+            _ => Err(format!("Unknown dimension: {s}")),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::ops::RangeInclusive;
+use crate::{FLOOR_HASH, ROOF_HASH};
+use crate::raw_data::block_type::BlockType;
+
+/// Where the nether bedrock gradients are and which randoms place them.
+/// Datapacks with custom noise_settings can shift or thicken the bedrock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BedrockProfile {
+    /// the floor is always bedrock at and below this y, the gradient starts above it
+    pub floor_y: i32,
+    /// the roof never has bedrock on this y, the gradient starts above it
+    pub roof_y: i32,
+    /// amount of layers until the gradient ends, the last layer of the roof is always bedrock
+    pub depth: i32,
+    /// java hash of the random name of the floor rule, e.g. minecraft:bedrock_floor, see random_hash
+    pub floor_hash: u64,
+    pub roof_hash: u64,
+}
+
+/// The hash the game mixes into the seed for a surface rule random, java String.hashCode
+/// of the name. Modded dimensions can name their vertical gradients however they like
+pub fn random_hash(name: &str) -> u64 {
+    let hash = name
+        .encode_utf16()
+        .fold(0i32, |hash, char| hash.wrapping_mul(31).wrapping_add(char as i32));
+    // the int gets sign extended when java xors it with the long seed
+    hash as i64 as u64
+}
+
+impl BedrockProfile {
+    /// the vanilla nether since 1.18
+    pub const NETHER: BedrockProfile = BedrockProfile {
+        floor_y: 0,
+        roof_y: 122,
+        depth: 5,
+        floor_hash: FLOOR_HASH,
+        roof_hash: ROOF_HASH,
+    };
+
+    /// every layer of the floor where the game decides about bedrock, including the solid one
+    pub fn floor_layers(&self) -> RangeInclusive<i32> {
+        self.floor_y..=self.floor_y + self.depth - 1
+    }
+
+    /// every layer of the roof where the game decides about bedrock, including the solid one
+    pub fn roof_layers(&self) -> RangeInclusive<i32> {
+        self.roof_y + 1..=self.roof_y + self.depth
+    }
+
+    /// the layers that are bedrock for every seed
+    pub fn is_solid(&self, y: i32) -> bool {
+        y == self.floor_y || y == self.roof_y + self.depth
+    }
+
+    /// whether a block belongs to the floor or the roof
+    pub fn is_floor(&self, y: i32) -> bool {
+        y < (self.floor_y + self.roof_y + self.depth) / 2
+    }
+
+    /// chance that the vertical gradient surface rule passes at this layer
+    /// the floor places bedrock if it passes, the roof if it fails
+    pub fn gradient(&self, y: i32) -> f64 {
+        let start = if self.is_floor(y) { self.floor_y } else { self.roof_y };
+        ((start + self.depth - y) as f64 / self.depth as f64).clamp(0.0, 1.0)
+    }
+
+    /// the block the rule places on layers outside of the gradients, it is the same for every seed
+    pub fn fixed_block(&self, y: i32) -> Option<BlockType> {
+        let passes_gradient = match self.gradient(y) {
+            chance if chance >= 1.0 => true,
+            chance if chance <= 0.0 => false,
+            _ => return None,
+        };
+        Some(if passes_gradient == self.is_floor(y) {
+            BlockType::BEDROCK
+        } else {
+            BlockType::OTHER
+        })
+    }
+
+    /// the y paper used for every block of a surface
+    pub fn paper_y(&self, y: i32) -> i32 {
+        if self.is_floor(y) { self.floor_y } else { self.roof_y }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.depth > 0 && self.floor_y + self.depth <= self.roof_y
+    }
+}
+
+impl Default for BedrockProfile {
+    fn default() -> Self {
+        BedrockProfile::NETHER
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nether() {
+        let profile = BedrockProfile::NETHER;
+        assert_eq!(profile.floor_layers(), 0..=4);
+        assert_eq!(profile.roof_layers(), 123..=127);
+        assert!(profile.is_solid(0) && profile.is_solid(127) && !profile.is_solid(4));
+        assert_eq!(profile.gradient(0), 1.0);
+        assert_eq!(profile.gradient(4), 0.2);
+        assert_eq!(profile.gradient(123), 0.8);
+        assert_eq!(profile.gradient(127), 0.0);
+        assert_eq!(profile.fixed_block(0), Some(BlockType::BEDROCK));
+        assert_eq!(profile.fixed_block(5), Some(BlockType::OTHER));
+        assert_eq!(profile.fixed_block(122), Some(BlockType::OTHER));
+        assert_eq!(profile.fixed_block(127), Some(BlockType::BEDROCK));
+        assert_eq!(profile.fixed_block(1), None);
+        assert_eq!(profile.paper_y(3), 0);
+        assert_eq!(profile.paper_y(125), 122);
+    }
+
+    #[test]
+    fn test_random_hash() {
+        assert_eq!(random_hash("minecraft:bedrock_floor"), FLOOR_HASH);
+        assert_eq!(random_hash("minecraft:bedrock_roof"), ROOF_HASH);
+        assert_eq!(random_hash(""), 0);
+        // "polygenelubricants" is a well known string with hashCode Integer.MIN_VALUE
+        assert_eq!(random_hash("polygenelubricants"), i32::MIN as i64 as u64);
+        assert_eq!(random_hash("é"), 233);
+    }
+}
@@ -0,0 +1,238 @@
+//! Tells collectors which blocks are worth checking next and when they can stop
+
+use std::collections::HashSet;
+use crate::MASK48;
+use crate::block_data::{known_ranges, BlockFilter};
+use crate::estimate::expected_results;
+use crate::generator::BedrockGenerator;
+use crate::import::imported_layers;
+use crate::raw_data::block::Block;
+use crate::raw_data::block_type::BlockType;
+use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+
+/// wrong seeds the estimate may still expect when the data counts as enough
+pub const ENOUGH_FALSE_RESULTS: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Suggestion {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// expected information of checking the position, whatever block is there
+    pub bits: f64,
+}
+
+/// A position where the candidates of a crack disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Distinction {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    /// the candidates that have bedrock there
+    pub bedrock: Vec<u64>,
+    pub other: Vec<u64>,
+}
+
+/// the middle of the collected blocks, suggestions are searched around it
+pub fn center(blocks: &[Block]) -> (i32, i32) {
+    let count = blocks.len().max(1) as i64;
+    let (x, z) = blocks
+        .iter()
+        .fold((0, 0), |(x, z), block| (x + block.x as i64, z + block.z as i64));
+    ((x / count) as i32, (z / count) as i32)
+}
+
+/// whether a crack most likely finds only the real seed
+pub fn has_enough_data(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode, tolerance: u32) -> bool {
+    expected_results(blocks, mode, profile, output, tolerance) - 1.0 < ENOUGH_FALSE_RESULTS
+}
+
+/// Positions within radius of x z that tell the most about the seed, best first.
+/// The middle layers of a gradient are a coin flip and tell the most, y=4 and y=123 are
+/// only worth a lot if there is bedrock. Positions that share a random with known blocks,
+/// like the rest of a column in paper mode, tell less
+pub fn suggest_blocks(blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, x: i32, z: i32, radius: i32, count: usize) -> Vec<Suggestion> {
+    let known = known_ranges(blocks, mode, profile);
+
+    let mut suggestions: Vec<_> = imported_layers(profile)
+        .flat_map(|y| {
+            (x - radius..=x + radius).flat_map(move |x| (z - radius..=z + radius).map(move |z| (x, y, z)))
+        })
+        .map(|(x, y, z)| {
+            let filter = BlockFilter::from(&Block::new(x, y, z, BlockType::BEDROCK), mode, profile);
+            let (lower, upper) = known
+                .get(&(profile.is_floor(y), filter.pos_hash()))
+                .copied()
+                .unwrap_or((0, MASK48));
+            let (bedrock_lower, bedrock_upper) = filter.pass_range();
+            let bedrock = bedrock_upper.min(upper).saturating_sub(bedrock_lower.max(lower));
+            let bits = match upper.saturating_sub(lower) {
+                0 => 0.0,
+                possible => entropy(bedrock as f64 / possible as f64),
+            };
+            Suggestion { x, y, z, bits }
+        })
+        .filter(|suggestion| suggestion.bits > 0.0)
+        .collect();
+
+    // nearly equal information, like the 40% and 60% layers, is sorted by the distance
+    suggestions.sort_by_key(|suggestion| {
+        let millibits = (suggestion.bits * 1000.0).round() as i64;
+        (-millibits, (suggestion.x - x).pow(2) + (suggestion.z - z).pow(2))
+    });
+    suggestions.truncate(count);
+    suggestions
+}
+
+/// A short checklist of positions near the blocks that tells the candidates of a crack apart.
+/// Every position splits as many of the still indistinguishable candidates as possible.
+/// surface is Some(is_floor) if the seeds are bedrock seeds of the floor or the roof, None for world and structure seeds
+pub fn distinguish_seeds(seeds: &[u64], blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, surface: Option<bool>, radius: i32, count: usize) -> Vec<Distinction> {
+    let generators: Vec<_> = seeds
+        .iter()
+        .map(|&seed| match surface {
+            // bedrock seeds only say something about the surface they belong to
+            Some(_) => BedrockGenerator::from_surface_seeds(seed, seed, mode, profile),
+            None => BedrockGenerator::with_profile(seed, mode, profile),
+        })
+        .collect();
+    let known: HashSet<_> = blocks.iter().map(|block| (block.x, block.y, block.z)).collect();
+
+    let (x, z) = center(blocks);
+    let positions: Vec<_> = imported_layers(profile)
+        .filter(|y| surface.is_none_or(|is_floor| profile.is_floor(*y) == is_floor))
+        .flat_map(|y| {
+            (x - radius..=x + radius).flat_map(move |x| (z - radius..=z + radius).map(move |z| (x, y, z)))
+        })
+        .filter(|position| !known.contains(position))
+        .map(|(x, y, z)| {
+            let bedrock: Vec<_> = generators
+                .iter()
+                .map(|generator| generator.block_at(x, y, z) == Some(BlockType::BEDROCK))
+                .collect();
+            ((x, y, z), bedrock)
+        })
+        .collect();
+
+    // candidates that no chosen position told apart yet
+    let mut classes = vec![(0..seeds.len()).collect::<Vec<_>>()];
+    let mut checklist = vec![];
+    while checklist.len() < count {
+        let split = |bedrock: &[bool]| -> usize {
+            classes
+                .iter()
+                .map(|class| {
+                    let with_bedrock = class.iter().filter(|seed| bedrock[**seed]).count();
+                    with_bedrock * (class.len() - with_bedrock)
+                })
+                .sum()
+        };
+        let distance = |(position_x, _, position_z): (i32, i32, i32)| (position_x - x).pow(2) + (position_z - z).pow(2);
+        let Some((position, bedrock)) = positions
+            .iter()
+            .map(|(position, bedrock)| (split(bedrock), position, bedrock))
+            .filter(|(pairs, _, _)| *pairs > 0)
+            .max_by_key(|(pairs, position, _)| (*pairs, -distance(**position)))
+            .map(|(_, position, bedrock)| (*position, bedrock))
+        else {
+            break;
+        };
+
+        classes = classes
+            .into_iter()
+            .flat_map(|class| {
+                let (with_bedrock, without): (Vec<_>, Vec<_>) = class.into_iter().partition(|seed| bedrock[*seed]);
+                [with_bedrock, without]
+            })
+            .filter(|class| !class.is_empty())
+            .collect();
+        let (x, y, z) = position;
+        let (with_bedrock, without): (Vec<_>, Vec<_>) = (0..seeds.len()).partition(|seed| bedrock[*seed]);
+        checklist.push(Distinction {
+            x,
+            y,
+            z,
+            bedrock: with_bedrock.into_iter().map(|seed| seeds[seed]).collect(),
+            other: without.into_iter().map(|seed| seeds[seed]).collect(),
+        });
+    }
+    checklist
+}
+
+/// expected bits of an outcome with the given chance
+fn entropy(chance: f64) -> f64 {
+    [chance, 1.0 - chance]
+        .into_iter()
+        .filter(|chance| *chance > 0.0)
+        .map(|chance| -chance * chance.log2())
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::BedrockGenerator;
+    use crate::layer::surface_seed;
+    use crate::benchmark::benchmark_blocks;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    #[test]
+    fn test_suggestions() {
+        let profile = BedrockProfile::NETHER;
+        let suggestions = suggest_blocks(&[], CrackerMode::Normal, profile, 10, 10, 2, 4);
+        assert_eq!(suggestions.len(), 4);
+        for suggestion in &suggestions {
+            // 40% or 60% chance for bedrock
+            assert!([2, 3, 124, 125].contains(&suggestion.y));
+            assert!((suggestion.bits - entropy(0.4)).abs() < 1e-6);
+            assert_eq!((suggestion.x, suggestion.z), (10, 10));
+        }
+
+        // a known position tells nothing new
+        let known = [Block::new(10, 2, 10, BlockType::BEDROCK)];
+        let suggestions = suggest_blocks(&known, CrackerMode::Normal, profile, 10, 10, 0, 10);
+        assert!(!suggestions.iter().any(|suggestion| suggestion.y == 2));
+
+        // paper shares the random of a column, bedrock on y=4 means bedrock below it
+        let suggestions = suggest_blocks(&[Block::new(10, 4, 10, BlockType::BEDROCK)], CrackerMode::Paper1_18, profile, 10, 10, 0, 10);
+        assert!(suggestions.iter().all(|suggestion| suggestion.y > 5));
+    }
+
+    #[test]
+    fn test_distinguish_seeds() {
+        let profile = BedrockProfile::NETHER;
+        let seeds = [WORLD_SEED, WORLD_SEED + 1, WORLD_SEED + 2];
+        let blocks = [Block::new(100, 4, 100, BlockType::BEDROCK)];
+        let checklist = distinguish_seeds(&seeds, &blocks, CrackerMode::Normal, profile, None, 4, 10);
+        assert!(!checklist.is_empty() && checklist.len() <= 2);
+
+        // following the checklist with the blocks of the real seed leaves only the real seed
+        let generator = BedrockGenerator::new(WORLD_SEED, CrackerMode::Normal);
+        let mut candidates = seeds.to_vec();
+        for Distinction { x, y, z, bedrock, other } in &checklist {
+            assert!((x - 100).abs() <= 4 && (z - 100).abs() <= 4);
+            assert_ne!((*x, *y, *z), (100, 4, 100));
+            let side = if generator.block_at(*x, *y, *z) == Some(BlockType::BEDROCK) { bedrock } else { other };
+            candidates.retain(|seed| side.contains(seed));
+        }
+        assert_eq!(candidates, vec![WORLD_SEED]);
+
+        // one candidate has nothing to tell apart
+        assert!(distinguish_seeds(&seeds[..1], &blocks, CrackerMode::Normal, profile, None, 4, 10).is_empty());
+
+        // roof seeds only tell the roof apart, even if the blocks are on the floor
+        let roof_seeds: Vec<_> = seeds.iter().map(|seed| surface_seed(*seed, profile.roof_hash) & MASK48).collect();
+        let checklist = distinguish_seeds(&roof_seeds, &blocks, CrackerMode::Normal, profile, Some(false), 4, 10);
+        assert!(!checklist.is_empty());
+        assert!(checklist.iter().all(|distinction| !profile.is_floor(distinction.y)));
+    }
+
+    #[test]
+    fn test_enough_data() {
+        let blocks = benchmark_blocks();
+        let profile = BedrockProfile::NETHER;
+        assert!(has_enough_data(&blocks, CrackerMode::Normal, profile, OutputMode::WorldSeed, 0));
+        assert!(!has_enough_data(&blocks[..10], CrackerMode::Normal, profile, OutputMode::WorldSeed, 0));
+    }
+}
@@ -0,0 +1,133 @@
+use crate::MASK48;
+use crate::block_data::BlockFilter;
+use crate::layer::{next_long, reverse_next_long, surface_seed};
+use crate::raw_data::block::Block;
+use crate::raw_data::modes::{CrackerMode, OutputMode};
+use crate::raw_data::profile::BedrockProfile;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub seed: u64,
+    pub matches: Vec<Block>,
+    pub contradictions: Vec<Block>,
+    /// whether the seed can be produced by the games seed generation
+    /// only checked for world seeds
+    pub valid_world_seed: Option<bool>,
+}
+
+impl VerificationReport {
+    pub fn is_match(&self) -> bool {
+        self.contradictions.is_empty() && self.valid_world_seed != Some(false)
+    }
+
+    /// fraction of blocks that match the seed
+    pub fn score(&self) -> f64 {
+        let total = self.matches.len() + self.contradictions.len();
+        if total == 0 {
+            return 1.0;
+        }
+        self.matches.len() as f64 / total as f64
+    }
+}
+
+/// Checks a single seed against blocks without running a search.
+/// Blocks use the same y range as the cracker.
+/// Bedrock seeds belong to a single surface, so the blocks should only come from that surface
+pub fn verify_seed(seed: u64, blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode) -> VerificationReport {
+    let (floor_seed, roof_seed) = if output == OutputMode::BedrockSeed {
+        (seed, seed)
+    } else {
+        (surface_seed(seed, profile.floor_hash), surface_seed(seed, profile.roof_hash))
+    };
+
+    let mut matches = vec![];
+    let mut contradictions = vec![];
+    for block in blocks {
+        let contradicts = if let Some(block_type) = profile.fixed_block(block.y) {
+            block.block_type != block_type
+        } else {
+            let bedrock_seed = if profile.is_floor(block.y) { floor_seed } else { roof_seed };
+            BlockFilter::from(block, mode, profile).create_check(0).check(bedrock_seed)
+        };
+        if contradicts {
+            contradictions.push(block.clone());
+        } else {
+            matches.push(block.clone());
+        }
+    }
+
+    let valid_world_seed = match output {
+        OutputMode::WorldSeed => Some(
+            reverse_next_long(seed & MASK48)
+                .into_iter()
+                .any(|prev_seed| next_long(prev_seed) == seed),
+        ),
+        OutputMode::StructureSeed | OutputMode::BedrockSeed => None,
+    };
+
+    VerificationReport {
+        seed,
+        matches,
+        contradictions,
+        valid_world_seed,
+    }
+}
+
+/// Verifies every seed and sorts them from best to worst match
+pub fn rank_seeds(seeds: &[u64], blocks: &[Block], mode: CrackerMode, profile: BedrockProfile, output: OutputMode) -> Vec<VerificationReport> {
+    let mut reports: Vec<_> = seeds
+        .iter()
+        .map(|&seed| verify_seed(seed, blocks, mode, profile, output))
+        .collect();
+    reports.sort_by_key(|report| (!report.is_match(), report.contradictions.len()));
+    reports
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::raw_data::block_type::BlockType;
+    use crate::WORLD_SEED;
+    use super::*;
+
+    const BLOCKS: [Block; 4] = [
+        Block::new(19, 123, -117, BlockType::BEDROCK),
+        Block::new(33, 126, -99, BlockType::OTHER),
+        Block::new(10, 1, -111, BlockType::BEDROCK),
+        Block::new(14, 4, -97, BlockType::OTHER),
+    ];
+
+    #[test]
+    fn test_verify_seed() {
+        let report = verify_seed(WORLD_SEED, &BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed);
+        assert!(report.is_match());
+        assert_eq!(report.matches.len(), 4);
+        assert_eq!(report.valid_world_seed, Some(true));
+
+        let structure_seed = WORLD_SEED & MASK48;
+        let report = verify_seed(structure_seed, &BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::StructureSeed);
+        assert!(report.is_match());
+        assert_eq!(report.valid_world_seed, None);
+    }
+
+    #[test]
+    fn test_contradiction() {
+        let mut blocks = BLOCKS.to_vec();
+        blocks[0].block_type = BlockType::OTHER;
+        let report = verify_seed(WORLD_SEED, &blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed);
+        assert!(!report.is_match());
+        assert_eq!(report.contradictions, vec![blocks[0].clone()]);
+        assert_eq!(report.score(), 0.75);
+
+        blocks[0].block_type = BlockType::BEDROCK;
+        blocks.push(Block::new(0, 127, 0, BlockType::BEDROCK));
+        blocks.push(Block::new(0, 0, 0, BlockType::OTHER));
+        let report = verify_seed(WORLD_SEED, &blocks, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::WorldSeed);
+        assert_eq!(report.contradictions, vec![blocks[5].clone()]);
+    }
+
+    #[test]
+    fn test_rank_seeds() {
+        let reports = rank_seeds(&[WORLD_SEED + 1, WORLD_SEED], &BLOCKS, CrackerMode::Normal, BedrockProfile::NETHER, OutputMode::StructureSeed);
+        assert_eq!(reports[0].seed, WORLD_SEED);
+    }
+}
@@ -1,35 +1,84 @@
 use crate::tab::bedrock::block_entry::{Block, BlockMessage};
+use crate::tab::bedrock::project::{Project, ProjectBlock, PROJECT_VERSION};
 use crate::tab::controls::{ApplicationTab, CrackerEvent, CrackerState, TabMessage};
+use crate::tab::input_error_style::TextInputErrorStyle;
 
 use async_std::fs::File;
 use async_std::task::spawn_blocking;
 use iced::futures::io::BufWriter;
 use iced::futures::{AsyncWriteExt, SinkExt};
-use iced::{futures, Element, Length};
-use iced_native::widget::{column, pick_list, text};
+use iced::{futures, Color, Element, Length};
+use iced_native::theme::TextInput;
+use iced_native::widget::{column, pick_list, text, text_input};
 use iced_native::{subscription, Padding, Subscription, row};
-use bedrock_cracker::{CrackProgress, estimate_result_amount, search_bedrock_pattern};
+use bedrock_cracker::handle::CancelToken;
+use bedrock_cracker::benchmark::Calibration;
+use bedrock_cracker::estimate::{Estimate, SearchEstimate};
+use bedrock_cracker::suggest::{center, distinguish_seeds, has_enough_data, suggest_blocks, Distinction, Suggestion};
+use bedrock_cracker::{CrackProgress, block_information, overworld, reports_floor_seeds, search_bedrock_pattern, surface_information};
 use bedrock_cracker::raw_data::block::Block as BlockInfo;
+use bedrock_cracker::raw_data::config::parse_config;
 
 use iced::widget::{Column, Scrollable};
+use std::fmt;
+use std::fmt::Formatter;
 use tokio::sync::mpsc::channel;
 use bedrock_cracker::raw_data::block_type::BlockType;
-use bedrock_cracker::raw_data::modes::{CrackerMode, OutputMode};
+use bedrock_cracker::raw_data::modes::{CrackerMode, Dimension, OutputMode};
+use bedrock_cracker::raw_data::profile::BedrockProfile;
+
+/// how far from the collected blocks positions get suggested
+const SUGGESTION_RADIUS: i32 = 8;
 
 #[derive(Debug, Default)]
 pub struct BdrkTab {
+    /// only for the overworld, the nether estimate depends on the thread count
     estimated_seeds: u64,
+    /// bits known about the floor and the roof seed
+    surface_information: (f64, f64),
+    /// advice on what to collect next, too slow to compute on every frame
+    next_steps: String,
+    /// only for the nether, the runtime still depends on the thread count
+    search_estimate: Option<SearchEstimate>,
     blocks: Vec<Block>,
     valid_blocks: Vec<BlockInfo>,
     mode: CrackerMode,
     output_mode: OutputMode,
+    tolerance: Tolerance,
+    dimension: Dimension,
+    profile: BedrockProfile,
+    /// only for the overworld, narrows the search down to 2^16 world seeds
+    structure_seed: String,
+    /// problems with the last loaded config
+    load_errors: Vec<String>,
 }
 
 #[derive(Debug, Clone)]
 pub enum BdrkMessage {
     Block(usize, BlockMessage),
+    Dimension(Dimension),
     CrackerMode(CrackerMode),
     OutputMode(OutputMode),
+    Tolerance(Tolerance),
+    StructureSeed(String),
+}
+
+/// amount of blocks a seed may contradict
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tolerance(u32);
+
+impl Tolerance {
+    const ALL: [Tolerance; 4] = [Tolerance(0), Tolerance(1), Tolerance(2), Tolerance(3)];
+}
+
+impl fmt::Display for Tolerance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            0 => write!(f, "Exact match"),
+            1 => write!(f, "1 wrong block"),
+            n => write!(f, "{} wrong blocks", n),
+        }
+    }
 }
 
 impl From<TabMessage> for BdrkMessage {
@@ -44,26 +93,90 @@ impl ApplicationTab for BdrkTab {
     type Message = BdrkMessage;
 
     fn new() -> Self {
-        Self {
+        let mut tab = Self {
             estimated_seeds: (1 << 48),
+            surface_information: (0.0, 0.0),
+            next_steps: String::new(),
+            search_estimate: None,
             blocks: vec![Block::new()],
             valid_blocks: Vec::new(),
             mode: CrackerMode::Normal,
             output_mode: OutputMode::WorldSeed,
+            tolerance: Tolerance::default(),
+            dimension: Dimension::Nether,
+            profile: BedrockProfile::NETHER,
+            structure_seed: String::new(),
+            load_errors: Vec::new(),
+        };
+        tab.update_analysis();
+        tab
+    }
+
+    fn load_config(&mut self, file: String) -> Option<u64> {
+        let mut threads = None;
+        let blocks: Vec<Block> = if Project::is_project(&file) {
+            // a broken project keeps the current blocks
+            let project = match Project::from_json(&file) {
+                Ok(project) => project,
+                Err(err) => {
+                    self.load_errors = vec![err];
+                    return None;
+                }
+            };
+            self.load_errors = vec![];
+            self.dimension = project.dimension;
+            self.profile = project.profile;
+            self.mode = project.mode;
+            self.output_mode = project.output_mode;
+            self.tolerance = Tolerance(project.tolerance);
+            threads = project.threads;
+            project.blocks.iter().map(Block::from).collect()
+        } else {
+            let (blocks, errors) = parse_config(&file, self.dimension, self.profile);
+            self.load_errors = errors.iter().map(ToString::to_string).collect();
+            blocks.iter().map(Block::from).collect()
+        };
+        self.blocks = blocks;
+        for block in self.blocks.iter_mut() {
+            block.set_dimension(self.dimension, self.profile);
         }
+        self.update_blocks();
+        threads
     }
 
-    fn load_config(&mut self, file: String) {
+    fn save_config(&self, threads: Option<u64>) -> Result<String, String> {
+        // the empty entry at the end isnt a row yet
+        let rows = self.blocks
+            .iter()
+            .filter(|block| !block.is_empty() || !block.label().is_empty());
         let mut blocks = vec![];
-        for line in file.lines().take(200) {
-            let block = Block::from(line);
-            blocks.push(block);
+        let mut invalid_rows = vec![];
+        for (i, block) in rows.enumerate() {
+            match block.is_valid_pos() {
+                Some(info) => blocks.push(ProjectBlock {
+                    block: info,
+                    label: block.label().to_string(),
+                }),
+                None => invalid_rows.push((i + 1).to_string()),
+            }
         }
-        self.blocks = blocks;
-        self.update_blocks();
+        if !invalid_rows.is_empty() {
+            return Err(format!("Couldnt save, fix the coordinates of row {} first", invalid_rows.join(", ")));
+        }
+        Ok(Project {
+            version: PROJECT_VERSION,
+            dimension: self.dimension,
+            profile: self.profile,
+            mode: self.mode,
+            output_mode: self.output_mode,
+            tolerance: self.tolerance.0,
+            threads,
+            blocks,
+        }
+        .to_json())
     }
 
-    fn save_config(&self) -> String {
+    fn save_text_config(&self) -> String {
         let mut content = String::new();
         for block in self.valid_blocks.iter() {
             content.push_str(&format!("{}\n", block))
@@ -71,6 +184,17 @@ impl ApplicationTab for BdrkTab {
         content
     }
 
+    fn profile(&self) -> BedrockProfile {
+        self.profile
+    }
+
+    fn output_mode(&self) -> OutputMode {
+        match self.dimension {
+            Dimension::Nether => self.output_mode,
+            Dimension::Overworld => OutputMode::WorldSeed,
+        }
+    }
+
     fn update(&mut self, message: Self::Message) {
         match message {
             BdrkMessage::Block(index, BlockMessage::Deleted) => {
@@ -84,22 +208,59 @@ impl ApplicationTab for BdrkTab {
                     self.update_blocks();
                 }
             }
+            BdrkMessage::Dimension(dimension) => {
+                self.dimension = dimension;
+                for block in self.blocks.iter_mut() {
+                    block.set_dimension(dimension, self.profile);
+                }
+                self.update_blocks()
+            }
             BdrkMessage::CrackerMode(mode) => {
                 self.mode = mode;
                 self.update_blocks()
             }
             BdrkMessage::OutputMode(mode) => {
                 self.output_mode = mode;
+                self.update_analysis();
+            }
+            BdrkMessage::Tolerance(tolerance) => {
+                self.tolerance = tolerance;
+                self.update_analysis();
+            }
+            BdrkMessage::StructureSeed(seed) => {
+                self.structure_seed = seed;
             }
         }
     }
 
-    fn view(&self) -> Element<TabMessage> {
-        let estimate = text(format!(
-            "Naively estimated results: {} seeds",
-            self.estimated_seeds
-        ))
-            .width(Length::Fill);
+    fn estimate(&self, threads: u64, calibration: &Calibration) -> Option<Estimate> {
+        self.search_estimate.map(|estimate| estimate.with_speed(threads, calibration))
+    }
+
+    fn seed_surface(&self) -> Option<bool> {
+        (self.output_mode() == OutputMode::BedrockSeed).then(|| reports_floor_seeds(&self.valid_blocks, self.mode, self.profile))
+    }
+
+    fn distinguish(&self, seeds: &[u64], surface: Option<bool>) -> Vec<Distinction> {
+        match self.dimension {
+            Dimension::Nether => distinguish_seeds(seeds, &self.valid_blocks, self.mode, self.profile, surface, SUGGESTION_RADIUS, 5),
+            Dimension::Overworld => vec![],
+        }
+    }
+
+    fn view(&self, threads: u64, calibration: &Calibration) -> Element<'_, TabMessage> {
+        let estimate = match self.estimate(threads, calibration) {
+            Some(Estimate { results, seconds }) => {
+                format!("Expected results: {results:.0} seeds, takes about {} on {threads} threads", format_duration(seconds))
+            }
+            None => format!("Naively estimated results: {} seeds", self.estimated_seeds),
+        };
+        let estimate = text(estimate).width(Length::Fill);
+        let dimension = pick_list(
+            &Dimension::ALL[..],
+            Some(self.dimension),
+            BdrkMessage::Dimension,
+        );
         let crack_mode = pick_list(
             &CrackerMode::ALL[..],
             Some(self.mode),
@@ -110,7 +271,41 @@ impl ApplicationTab for BdrkTab {
             Some(self.output_mode),
             BdrkMessage::OutputMode,
         );
-        let top_bar = row![estimate, crack_mode, output_mode];
+        let tolerance = pick_list(
+            &Tolerance::ALL[..],
+            Some(self.tolerance),
+            BdrkMessage::Tolerance,
+        );
+        let top_bar = match self.dimension {
+            Dimension::Nether => row![estimate, dimension, crack_mode, output_mode, tolerance],
+            // the overworld always uses the vanilla random and reports world seeds
+            Dimension::Overworld => {
+                let mut structure_seed = text_input("Structure seed", &self.structure_seed)
+                    .on_input(BdrkMessage::StructureSeed)
+                    .width(Length::Fixed(200.0));
+                if !self.structure_seed.trim().is_empty() && self.structure_seed().is_none() {
+                    structure_seed = structure_seed.style(TextInput::Custom(Box::new(TextInputErrorStyle)));
+                }
+                row![estimate, dimension, structure_seed, tolerance]
+            }
+        };
+        let mut children = vec![top_bar.into()];
+        if self.dimension == Dimension::Overworld && self.structure_seed().is_none() {
+            children.push(text("Without a structure seed all 2^64 world seeds have to be searched, this takes years. The nether cracker finds the structure seed").into());
+        }
+        if self.dimension == Dimension::Nether {
+            let (floor, roof) = self.surface_information;
+            children.push(text(format!("Information: floor {floor:.1} / 48 bits, roof {roof:.1} / 48 bits")).into());
+            children.push(text(&self.next_steps).into());
+        }
+        if self.dimension == Dimension::Nether && self.profile != BedrockProfile::NETHER {
+            let BedrockProfile { floor_y, roof_y, depth, .. } = self.profile;
+            children.push(text(format!("Custom bedrock layers from the project: floor y={floor_y}, roof y={roof_y}, gradient depth {depth}")).into());
+        }
+        if self.dimension == Dimension::Nether && self.output_mode == OutputMode::BedrockSeed {
+            let surface = if reports_floor_seeds(&self.valid_blocks, self.mode, self.profile) { "floor" } else { "roof" };
+            children.push(text(format!("The results are bedrock seeds of the {surface}, they can be combined with seeds of the other surface later")).into());
+        }
         let coords: Element<_> = column(
             self.blocks
                 .iter()
@@ -125,7 +320,15 @@ impl ApplicationTab for BdrkTab {
         .spacing(5)
         .into();
         let coords = Scrollable::new(coords).height(Length::Fill);
-        let view: Element<_> = Column::with_children(vec![top_bar.into(), coords.into()]).into();
+        if !self.load_errors.is_empty() {
+            let errors = self.load_errors
+                .iter()
+                .map(|err| text(err).style(Color::from_rgb(0.8, 0.2, 0.2)).into())
+                .collect();
+            children.push(column(errors).padding(Padding::from([5, 20])).into());
+        }
+        children.push(coords.into());
+        let view: Element<_> = Column::with_children(children).into();
         view.map(TabMessage::BdrkMessage)
     }
 
@@ -135,9 +338,18 @@ impl ApplicationTab for BdrkTab {
             CrackerState::Starting(file_output) => {
                 let threads = threads.parse::<u64>().unwrap_or(1);
 
-                crack(&self.valid_blocks, file_output, threads, self.mode, self.output_mode)
+                let settings = CrackSettings {
+                    threads,
+                    dimension: self.dimension,
+                    profile: self.profile,
+                    mode: self.mode,
+                    output_mode: self.output_mode,
+                    tolerance: self.tolerance.0,
+                    structure_seed: self.structure_seed(),
+                };
+                crack(&self.valid_blocks, file_output, settings)
             }
-            CrackerState::Running => subscription::run_with_id(
+            CrackerState::Running | CrackerState::Paused => subscription::run_with_id(
                 std::any::TypeId::of::<Unique>(),
                 futures::stream::pending(),
             ),
@@ -146,20 +358,52 @@ impl ApplicationTab for BdrkTab {
 }
 
 impl BdrkTab {
+    fn structure_seed(&self) -> Option<u64> {
+        self.structure_seed.trim().parse::<i64>().ok().map(|seed| seed as u64)
+    }
+
+    /// the positions that help the most, around the blocks that were already collected
+    fn next_steps(&self) -> String {
+        if has_enough_data(&self.valid_blocks, self.mode, self.profile, self.output_mode, self.tolerance.0) {
+            return "Enough data, the crack should only find the real seed".to_string();
+        }
+        let (x, z) = center(&self.valid_blocks);
+        let suggestions = suggest_blocks(&self.valid_blocks, self.mode, self.profile, x, z, SUGGESTION_RADIUS, 5);
+        let positions: Vec<_> = suggestions
+            .iter()
+            .map(|Suggestion { x, y, z, .. }| format!("{x} {y} {z}"))
+            .collect();
+        format!("Most useful positions to check next: {}", positions.join(", "))
+    }
+
     fn update_blocks(&mut self) {
         self.add_entry();
         self.update_invalid_states();
-        self.estimated_seeds = estimate_result_amount(&self.valid_blocks).max(1);
+        if self.dimension == Dimension::Overworld {
+            self.estimated_seeds = overworld::estimate_result_amount(&self.valid_blocks).max(1);
+        }
+        self.update_analysis();
+    }
+
+    /// everything derived from the valid blocks and the settings
+    fn update_analysis(&mut self) {
+        self.surface_information = surface_information(&self.valid_blocks, self.mode, self.profile);
+        (self.next_steps, self.search_estimate) = match self.dimension {
+            Dimension::Nether => (
+                self.next_steps(),
+                Some(SearchEstimate::new(&self.valid_blocks, self.mode, self.profile, self.output_mode, self.tolerance.0)),
+            ),
+            Dimension::Overworld => (String::new(), None),
+        };
     }
 
     fn add_entry(&mut self) {
-        if let Some(last_block) = self.blocks.last() {
-            if !last_block.is_empty() {
-                self.blocks.push(Block::new())
-            }
-        } else {
-            self.blocks.push(Block::new())
+        if self.blocks.last().is_some_and(|last_block| last_block.is_empty()) {
+            return;
         }
+        let mut block = Block::new();
+        block.set_dimension(self.dimension, self.profile);
+        self.blocks.push(block)
     }
 
     /// check for multiple blocks in the same position etc...
@@ -167,28 +411,42 @@ impl BdrkTab {
         let mut valid_blocks: Vec<BlockInfo> = vec![];
         for gui_block in self.blocks.iter_mut() {
             if let Some(block) = gui_block.is_valid_pos() {
-                let invalid = Self::check_invalid(&block, &valid_blocks, self.mode);
+                let invalid = match self.dimension {
+                    Dimension::Nether => Self::check_invalid(&block, &valid_blocks, self.mode, self.profile),
+                    Dimension::Overworld => !overworld::is_possible(&block) || valid_blocks.iter().any(|valid_block| {
+                        (block.x, block.y, block.z) == (valid_block.x, valid_block.y, valid_block.z)
+                    }),
+                };
+                let information = (self.dimension == Dimension::Nether && !invalid)
+                    .then(|| block_information(&block, self.mode, self.profile));
                 if !invalid {
                     valid_blocks.push(block);
                 }
                 gui_block.set_duplicate(invalid);
+                gui_block.set_information(information);
+            } else {
+                gui_block.set_information(None);
             }
         }
         self.valid_blocks = valid_blocks;
     }
 
-    fn check_invalid(block: &BlockInfo, valid_blocks: &[BlockInfo], mode: CrackerMode) -> bool {
-        for valid_block in valid_blocks.iter() {
+    fn check_invalid(block: &BlockInfo, valid_blocks: &[BlockInfo], mode: CrackerMode, profile: BedrockProfile) -> bool {
+        // the solid layers are always bedrock and dont tell anything about the rest of the column
+        if profile.is_solid(block.y) {
+            return block.block_type != BlockType::BEDROCK || valid_blocks.contains(block);
+        }
+        for valid_block in valid_blocks.iter().filter(|valid_block| !profile.is_solid(valid_block.y)) {
             if block.x == valid_block.x &&
                 block.z == valid_block.z &&
-                (block.y > 5) == (valid_block.y > 5)
+                profile.is_floor(block.y) == profile.is_floor(valid_block.y)
             {
                 if block.y == valid_block.y { return true }
                 if mode == CrackerMode::Paper1_18 {
                     if block.block_type == valid_block.block_type { return true }
                     let mut y1 = valid_block.y;
                     let mut y2 = block.y;
-                    if (y1 > 5) ^ (block.block_type == BlockType::OTHER) {
+                    if !profile.is_floor(y1) ^ (block.block_type == BlockType::OTHER) {
                         (y1, y2) = (y2, y1);
                     }
                     if y1 <= y2 { return true }
@@ -201,15 +459,24 @@ impl BdrkTab {
 
 struct Unique;
 
-pub fn crack(
-    blocks: &Vec<BlockInfo>,
-    file_output: &Option<String>,
+#[derive(Debug, Clone, Copy)]
+pub struct CrackSettings {
     threads: u64,
+    dimension: Dimension,
+    profile: BedrockProfile,
     mode: CrackerMode,
     output_mode: OutputMode,
+    tolerance: u32,
+    structure_seed: Option<u64>,
+}
+
+pub fn crack(
+    blocks: &[BlockInfo],
+    file_output: &Option<String>,
+    settings: CrackSettings,
 ) -> Subscription<CrackerEvent> {
     let file_output = file_output.clone();
-    let blocks: Vec<_> = blocks.clone();
+    let blocks: Vec<_> = blocks.to_vec();
 
     subscription::channel(std::any::TypeId::of::<Unique>(), 100, move |mut output| {
         let file_output = file_output.clone();
@@ -217,15 +484,23 @@ pub fn crack(
         async move {
             let mut writer = create_file_writer(&file_output).await;
 
+            let (sender, mut receiver) = channel(100);
+
+            let handle = spawn_blocking(move || {
+                let CrackSettings { threads, dimension, profile, mode, output_mode, tolerance, structure_seed } = settings;
+                match dimension {
+                    Dimension::Nether => search_bedrock_pattern(&blocks, threads, mode, profile, output_mode, tolerance, sender),
+                    Dimension::Overworld => overworld::search_overworld_pattern(&blocks, threads, structure_seed, tolerance, sender),
+                }
+            }).await;
+            // iced drops this future when the crack gets stopped
+            let _cancel = CancelOnDrop(handle.token());
+
             output
-                .send(CrackerEvent::Started)
+                .send(CrackerEvent::Started(handle.token()))
                 .await
                 .expect("TODO: panic message");
 
-            let (sender, mut receiver) = channel(100);
-
-            spawn_blocking(move || search_bedrock_pattern(&blocks, threads, mode, output_mode, sender));
-
             let mut seeds = vec![];
             while let Some(pl_event) = receiver.recv().await {
                 match pl_event {
@@ -237,12 +512,16 @@ pub fn crack(
                     }
                     CrackProgress::Seed(num) => {
                         let seed = (num as i64).to_string();
-                        if let Some(ref mut writer) = writer {
-                            let line = format!("{}\n", seed);
-                            writer.write_all(line.as_bytes()).await.unwrap();
-                        }
+                        write_seed(&mut writer, &seed).await;
                         seeds.push(seed);
                     }
+                    CrackProgress::PartialMatch(num, mismatches) => {
+                        let seed = format!("{} ({} mismatches)", num as i64, mismatches);
+                        write_seed(&mut writer, &seed).await;
+                        seeds.push(seed);
+                    }
+                    CrackProgress::ChunkDone(_) => {}
+                    CrackProgress::Finished | CrackProgress::Cancelled => break,
                 };
             }
             output.send(CrackerEvent::Finished).await.unwrap();
@@ -252,6 +531,31 @@ pub fn crack(
     })
 }
 
+pub fn format_duration(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    match seconds {
+        0..=59 => format!("{seconds}s"),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        3600..=86399 => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+        _ => format!("{}d {}h", seconds / 86400, seconds % 86400 / 3600),
+    }
+}
+
+struct CancelOnDrop(CancelToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+async fn write_seed(writer: &mut Option<BufWriter<File>>, seed: &str) {
+    if let Some(ref mut writer) = writer {
+        let line = format!("{}\n", seed);
+        writer.write_all(line.as_bytes()).await.unwrap();
+    }
+}
+
 async fn create_file_writer(file: &Option<String>) -> Option<BufWriter<File>> {
     match file {
         None => None,
@@ -266,6 +570,14 @@ async fn create_file_writer(file: &Option<String>) -> Option<BufWriter<File>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.4), "0s");
+        assert_eq!(format_duration(2814.0), "46m 54s");
+        assert_eq!(format_duration(7300.0), "2h 1m");
+        assert_eq!(format_duration(3.0 * 86400.0 + 7200.0), "3d 2h");
+    }
+
     #[test]
     fn test_check_invalid() {
         let block = BlockInfo::new(1,1,1,BlockType::BEDROCK);
@@ -274,11 +586,57 @@ mod tests {
             BlockInfo::new(1,3,1,BlockType::BEDROCK)
         ];
         //no duplicates -> block is valid
-        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Normal));
+        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Normal, BedrockProfile::NETHER));
 
         //duplicates -> block is invalid
         valid_blocks.push(block.clone());
-        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Normal));
+        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Normal, BedrockProfile::NETHER));
+    }
+
+    #[test]
+    fn test_load_text_config() {
+        let mut tab = BdrkTab::new();
+        assert_eq!(tab.load_config("1 2 3 Bedrock\n1 5 3 Other\n4 4 4 Other\n".to_string()), None);
+        assert_eq!(tab.valid_blocks, vec![
+            BlockInfo::new(1, 2, 3, BlockType::BEDROCK),
+            BlockInfo::new(4, 4, 4, BlockType::OTHER),
+        ]);
+        assert_eq!(tab.load_errors, vec!["line 2: y=5 is not a bedrock layer".to_string()]);
+
+        tab.load_config("{".to_string());
+        assert_eq!(tab.valid_blocks.len(), 2);
+        assert_eq!(tab.load_errors.len(), 1);
+    }
+
+    #[test]
+    fn test_save_invalid_rows() {
+        let mut tab = BdrkTab::new();
+        tab.load_config("1 2 3 Bedrock\n".to_string());
+        assert!(tab.save_config(None).is_ok());
+
+        // a half typed block with a note isnt dropped silently
+        tab.update(BdrkMessage::Block(1, BlockMessage::EditedLabel("lava lake".to_string())));
+        tab.update(BdrkMessage::Block(1, BlockMessage::EditedX("5".to_string())));
+        assert_eq!(tab.save_config(None), Err("Couldnt save, fix the coordinates of row 2 first".to_string()));
+    }
+
+    #[test]
+    fn test_overworld_inputs() {
+        let mut tab = BdrkTab::new();
+        tab.update(BdrkMessage::Dimension(Dimension::Overworld));
+        tab.update(BdrkMessage::OutputMode(OutputMode::BedrockSeed));
+        tab.load_config("1 -62 1 Bedrock\n1 3 1 Deepslate\n".to_string());
+        let mut bedrock = Block::from(&BlockInfo::new(2, 3, 2, BlockType::BEDROCK));
+        bedrock.set_dimension(Dimension::Overworld, tab.profile);
+        tab.blocks.insert(0, bedrock);
+        tab.update_blocks();
+        // bedrock doesnt generate on the deepslate layers
+        assert_eq!(tab.valid_blocks.len(), 2);
+        assert_eq!(tab.output_mode(), OutputMode::WorldSeed);
+
+        assert_eq!(tab.structure_seed(), None);
+        tab.update(BdrkMessage::StructureSeed(" -12 ".to_string()));
+        assert_eq!(tab.structure_seed(), Some(-12i64 as u64));
     }
 
     #[test]
@@ -288,18 +646,24 @@ mod tests {
             BlockInfo::new(1,2,1,BlockType::OTHER)
         ];
         //valid position
-        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18));
+        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
 
         //bedrock ont op of other is an invalid placement
         block.y = 3;
-        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18));
+        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
 
         //Two of the same type in the same column is redundant
         block.block_type = BlockType::OTHER;
-        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18));
+        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
 
         //valid on opposite sites
         block.y = 123;
-        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18));
+        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
+
+        //the solid layers only need to be bedrock
+        block.y = 0;
+        assert!(BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
+        block.block_type = BlockType::BEDROCK;
+        assert!(!BdrkTab::check_invalid(&block, &valid_blocks, CrackerMode::Paper1_18, BedrockProfile::NETHER));
     }
 }
@@ -1,10 +1,14 @@
 use crate::tab::input_error_style::TextInputErrorStyle;
-use iced::{widget, Element, Renderer, Theme};
+use iced::{widget, Element, Length, Renderer, Theme};
 use iced_native::row;
 use iced_native::theme::TextInput;
-use iced_native::widget::{button, pick_list, text_input};
+use iced_native::widget::{button, pick_list, text, text_input};
 use bedrock_cracker::raw_data::block_type::BlockType;
 use bedrock_cracker::raw_data::block::Block as BlockInfo;
+use bedrock_cracker::raw_data::config::is_valid_y;
+use bedrock_cracker::raw_data::modes::Dimension;
+use bedrock_cracker::raw_data::profile::BedrockProfile;
+use crate::tab::bedrock::project::ProjectBlock;
 
 
 #[derive(Debug, Clone)]
@@ -13,7 +17,11 @@ pub struct Block {
     y: Coord,
     z: Coord,
     block_type: BlockType,
+    label: String,
     duplicate: bool,
+    dimension: Dimension,
+    /// bits the block tells about its surface, None if the cracker doesnt use it
+    information: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -36,14 +44,14 @@ impl Coord {
 #[derive(Debug, Clone)]
 enum CoordType {
     XZ,
-    Y,
+    Y(Dimension, BedrockProfile),
 }
 
 impl CoordType {
     fn check_valid(&self, coord: i32) -> bool {
         match self {
             CoordType::XZ => (-30000000..=30000000).contains(&coord),
-            CoordType::Y => (1..=4).contains(&coord) || (123..=126).contains(&coord),
+            CoordType::Y(dimension, profile) => is_valid_y(coord, *dimension, *profile),
         }
     }
 }
@@ -68,12 +76,8 @@ impl Coord {
     {
         let mut coord = text_input("", &self.text).on_input(callback);
 
-        if !last {
-            if duplicate {
-                coord = coord.style(TextInput::Custom(Box::new(TextInputErrorStyle)));
-            } else if self.num.is_none() {
-                coord = coord.style(TextInput::Custom(Box::new(TextInputErrorStyle)));
-            }
+        if !last && (duplicate || self.num.is_none()) {
+            coord = coord.style(TextInput::Custom(Box::new(TextInputErrorStyle)));
         }
 
         coord
@@ -86,6 +90,7 @@ pub enum BlockMessage {
     EditedY(String),
     EditedZ(String),
     EditedType(BlockType),
+    EditedLabel(String),
     Deleted,
 }
 
@@ -93,10 +98,22 @@ impl Block {
     pub fn new() -> Self {
         Self {
             x: Coord::new(CoordType::XZ),
-            y: Coord::new(CoordType::Y),
+            y: Coord::new(CoordType::Y(Dimension::Nether, BedrockProfile::NETHER)),
             z: Coord::new(CoordType::XZ),
             block_type: BlockType::BEDROCK,
+            label: String::new(),
             duplicate: false,
+            dimension: Dimension::Nether,
+            information: None,
+        }
+    }
+
+    pub fn set_dimension(&mut self, dimension: Dimension, profile: BedrockProfile) {
+        self.dimension = dimension;
+        self.y.coord_type = CoordType::Y(dimension, profile);
+        self.y.update(self.y.text.clone());
+        if dimension == Dimension::Nether && self.block_type == BlockType::DEEPSLATE {
+            self.block_type = BlockType::OTHER;
         }
     }
 
@@ -111,29 +128,37 @@ impl Block {
             BlockMessage::EditedType(block_type) => {
                 self.block_type = block_type;
             }
+            BlockMessage::EditedLabel(label) => self.label = label,
         }
     }
 
-    pub fn view(&self, last: bool) -> Element<BlockMessage> {
+    pub fn view(&self, last: bool) -> Element<'_, BlockMessage> {
         let x = self.x.view(last, self.duplicate, BlockMessage::EditedX);
         let y = self.y.view(last, self.duplicate, BlockMessage::EditedY);
         let z = self.z.view(last, self.duplicate, BlockMessage::EditedZ);
+        let block_types = match self.dimension {
+            Dimension::Nether => &BlockType::NETHER[..],
+            Dimension::Overworld => &BlockType::ALL[..],
+        };
         let selection = pick_list(
-            &BlockType::ALL[..],
+            block_types,
             Some(self.block_type),
             BlockMessage::EditedType,
         );
+        let label = text_input("Note", &self.label).on_input(BlockMessage::EditedLabel);
+        let information = text(self.information.map_or(String::new(), |bits| format!("{bits:.2} bits")))
+            .width(Length::Fixed(70.0));
         let mut delete = button("Delete");
 
         if !last {
             delete = delete.on_press(BlockMessage::Deleted);
         }
 
-        row![x, y, z, selection, delete].spacing(5).into()
+        row![x, y, z, selection, label, information, delete].spacing(5).into()
     }
 
     pub fn is_empty(&self) -> bool {
-        &self.x.text == "" && self.y.text == "" && self.z.text == ""
+        self.x.text.is_empty() && self.y.text.is_empty() && self.z.text.is_empty()
     }
 
     pub fn is_valid_pos(&self) -> Option<BlockInfo> {
@@ -143,29 +168,34 @@ impl Block {
         }
     }
 
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
     pub fn set_duplicate(&mut self, duplicate: bool) {
         self.duplicate = duplicate;
     }
+
+    pub fn set_information(&mut self, information: Option<f64>) {
+        self.information = information;
+    }
 }
 
-impl From<&str> for Block {
-    fn from(line: &str) -> Self {
+impl From<&BlockInfo> for Block {
+    fn from(info: &BlockInfo) -> Self {
         let mut block = Block::new();
-        let mut components = line.split_whitespace();
-        block
-            .x
-            .update(components.next().unwrap_or_default().to_string());
-        block
-            .y
-            .update(components.next().unwrap_or_default().to_string());
+        block.x.update(info.x.to_string());
+        block.y.update(info.y.to_string());
+        block.z.update(info.z.to_string());
+        block.block_type = info.block_type;
         block
-            .z
-            .update(components.next().unwrap_or_default().to_string());
-        if components.next().unwrap_or_default().to_ascii_lowercase()
-            == format!("{}", BlockType::OTHER).to_ascii_lowercase()
-        {
-            block.block_type = BlockType::OTHER;
-        }
+    }
+}
+
+impl From<&ProjectBlock> for Block {
+    fn from(project_block: &ProjectBlock) -> Self {
+        let mut block = Block::from(&project_block.block);
+        block.label = project_block.label.clone();
         block
     }
 }
@@ -1,2 +1,3 @@
 pub mod bedrock_tab;
 mod block_entry;
+pub mod project;
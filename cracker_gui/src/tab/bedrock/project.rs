@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use bedrock_cracker::raw_data::block::Block;
+use bedrock_cracker::raw_data::modes::{CrackerMode, Dimension, OutputMode};
+use bedrock_cracker::raw_data::profile::BedrockProfile;
+
+/// increase when the format changes in a way older versions cant read
+pub const PROJECT_VERSION: u32 = 1;
+
+/// Everything needed to continue working on a crack, saved as json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    #[serde(default)]
+    pub dimension: Dimension,
+    /// only needed for datapacks that move the nether bedrock
+    #[serde(default, skip_serializing_if = "is_vanilla")]
+    pub profile: BedrockProfile,
+    #[serde(default)]
+    pub mode: CrackerMode,
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    #[serde(default)]
+    pub tolerance: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<u64>,
+    pub blocks: Vec<ProjectBlock>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectBlock {
+    #[serde(flatten)]
+    pub block: Block,
+    /// free-form note, e.g. where the block was found
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub label: String,
+}
+
+fn is_vanilla(profile: &BedrockProfile) -> bool {
+    *profile == BedrockProfile::NETHER
+}
+
+impl Project {
+    /// legacy configs are plain text, one block per line
+    pub fn is_project(file: &str) -> bool {
+        file.trim_start().starts_with('{')
+    }
+
+    pub fn from_json(file: &str) -> Result<Project, String> {
+        let project: Project = serde_json::from_str(file).map_err(|err| format!("Invalid project file: {err}"))?;
+        if project.version > PROJECT_VERSION {
+            return Err(format!(
+                "The project was saved by a newer version (format {}), please update the cracker",
+                project.version
+            ));
+        }
+        if !project.profile.is_valid() {
+            return Err("The bedrock layers of the project overlap or are empty".to_string());
+        }
+        Ok(project)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("projects are always serializable")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bedrock_cracker::raw_data::block_type::BlockType;
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let project = Project {
+            version: PROJECT_VERSION,
+            dimension: Dimension::Nether,
+            profile: BedrockProfile { floor_y: -64, depth: 8, ..BedrockProfile::NETHER },
+            mode: CrackerMode::Paper1_18,
+            output_mode: OutputMode::StructureSeed,
+            tolerance: 1,
+            threads: Some(6),
+            blocks: vec![
+                ProjectBlock { block: Block::new(19, 123, -117, BlockType::BEDROCK), label: "portal".to_string() },
+                ProjectBlock { block: Block::new(10, 1, -111, BlockType::OTHER), label: String::new() },
+            ],
+        };
+        let json = project.to_json();
+        assert!(Project::is_project(&json));
+        assert_eq!(Project::from_json(&json).unwrap(), project);
+
+        let newer = json.replace(&format!("\"version\": {PROJECT_VERSION}"), "\"version\": 999");
+        assert!(Project::from_json(&newer).is_err());
+    }
+
+    #[test]
+    fn test_defaults() {
+        let project = Project::from_json(r#"{"version": 1, "blocks": [{"x": 1, "y": 2, "z": 3, "block_type": "BEDROCK"}]}"#).unwrap();
+        assert_eq!(project.mode, CrackerMode::Normal);
+        assert_eq!(project.threads, None);
+        assert_eq!(project.profile, BedrockProfile::NETHER);
+        assert_eq!(project.blocks[0].block, Block::new(1, 2, 3, BlockType::BEDROCK));
+        assert!(!Project::is_project("1 2 3 Bedrock"));
+    }
+}
@@ -1,14 +1,26 @@
-use crate::tab::bedrock::bedrock_tab::{BdrkMessage};
-
-use async_std::fs;
+use crate::tab::bedrock::bedrock_tab::{format_duration, BdrkMessage};
+
+use async_std::{fs, task};
+use bedrock_cracker::benchmark::{calibrate, Calibration};
+use bedrock_cracker::estimate::Estimate;
+use bedrock_cracker::handle::CancelToken;
+use bedrock_cracker::import::anvil::read_nether;
+use bedrock_cracker::import::schematic::read_schematic;
+use bedrock_cracker::intersect::{intersect_bedrock_seeds, parse_seed_list};
+use bedrock_cracker::raw_data::block_type::BlockType;
+use bedrock_cracker::raw_data::modes::OutputMode;
+use bedrock_cracker::suggest::Distinction;
+use bedrock_cracker::raw_data::profile::BedrockProfile;
 use iced::alignment::Horizontal;
 use iced::widget::{Column, Container, Row, Scrollable};
 use iced::{Element, Length};
 use iced_native::alignment::Vertical;
 use iced_native::widget::{button, progress_bar, text, text_input};
 use iced_native::{Command, Subscription};
+use directories_next::ProjectDirs;
 use rfd::AsyncFileDialog;
 use std::fmt;
+use std::path::PathBuf;
 use std::fmt::{Debug, Formatter};
 use std::time::{Duration, Instant};
 
@@ -16,20 +28,77 @@ use std::time::{Duration, Instant};
 pub struct ControlMenu<Tab> {
     tab: Tab,
     cracking: CrackerState,
+    cracker: Option<CancelToken>,
     threads: String,
+    schematic_origin: String,
+    /// the area "Import world" reads, empty means DEFAULT_WORLD_AREA
+    world_area: String,
     crack_data: Option<MetaData>,
+    combine_error: Option<String>,
+    import_error: Option<String>,
+    save_error: Option<String>,
+    /// speed of this machine, the estimate uses it for the runtime
+    calibration: Calibration,
+    benchmarking: bool,
+    /// positions that tell the results apart
+    checklist: Vec<Distinction>,
+}
+
+/// blocks at the nether spawn, they are usually generated
+const DEFAULT_WORLD_AREA: [i32; 4] = [0, 0, 3, 3];
+
+/// more results than this need more blocks instead of a checklist
+const MAX_DISTINGUISHED: usize = 64;
+
+/// per thread count, the benchmark measures one thread and the selected amount
+const BENCHMARK_DURATION: Duration = Duration::from_secs(3);
+
+fn calibration_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "Nether Bedrock Cracker").map(|dirs| dirs.config_dir().join("calibration.txt"))
+}
+
+/// a missing or broken calibration falls back to the default speed
+fn load_calibration() -> Calibration {
+    calibration_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|file| Calibration::parse(&file).ok())
+        .unwrap_or_default()
+}
+
+fn save_calibration(calibration: &Calibration) {
+    let Some(path) = calibration_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    // without the file the next start just uses the default speed again
+    let _ = std::fs::write(path, calibration.to_string());
 }
 
 #[derive(Debug, Clone)]
 pub enum ControlMessage {
     TabMessage(TabMessage),
     CrackButton(bool),
+    PauseButton,
     CrackerMessage(CrackerEvent),
     CrackStart(Option<String>),
     ThreadCount(String),
     LoadConfig,
+    WorldArea(String),
+    ImportWorld,
+    ImportedWorld(Result<String, String>),
+    SchematicOrigin(String),
+    ImportSchematic,
     LoadedConfig(Option<String>),
     SaveConfig,
+    SavedConfig(Result<(), String>),
+    CombineResults,
+    /// the results and their surface if they are bedrock seeds
+    CombinedResults(Result<(Vec<String>, Option<bool>), String>),
+    Benchmark,
+    Benchmarked(Calibration),
+    Observed(usize, BlockType),
     None,
 }
 
@@ -42,14 +111,23 @@ where
         Self {
             tab: Tab::new(),
             cracking: CrackerState::Idle,
+            cracker: None,
             threads: "".to_string(),
+            schematic_origin: "".to_string(),
+            world_area: "".to_string(),
             crack_data: None,
+            combine_error: None,
+            import_error: None,
+            save_error: None,
+            calibration: load_calibration(),
+            benchmarking: false,
+            checklist: vec![],
         }
     }
 
-    pub fn view(&self) -> Element<ControlMessage> {
+    pub fn view(&self) -> Element<'_, ControlMessage> {
         // coords
-        let coord_list = self.tab.view().map(ControlMessage::TabMessage);
+        let coord_list = self.tab.view(self.thread_count(), &self.calibration).map(ControlMessage::TabMessage);
 
         let coord_list: Element<_> = Container::new(coord_list)
             .width(Length::FillPortion(3))
@@ -64,6 +142,24 @@ where
 
                 results = results.push(text("Results:").size(30));
 
+                if !self.checklist.is_empty() {
+                    results = results.push(text("Check these blocks to tell the results apart:").size(20));
+                }
+                for (i, distinction) in self.checklist.iter().enumerate() {
+                    let Distinction { x, y, z, bedrock, other } = distinction;
+                    let check = text(format!(
+                        "{x} {y} {z}: bedrock means {}, other means {}",
+                        describe_seeds(bedrock),
+                        describe_seeds(other)
+                    ));
+                    let row = Row::new()
+                        .push(check.width(Length::Fill))
+                        .push(button("Bedrock").on_press(ControlMessage::Observed(i, BlockType::BEDROCK)))
+                        .push(button("Other").on_press(ControlMessage::Observed(i, BlockType::OTHER)))
+                        .spacing(5);
+                    results = results.push(row);
+                }
+
                 if meta_data.results.len() == 1000 {
                     results = results.push(
                         text("Not printing more than 1000 results, consider printing to a file")
@@ -109,8 +205,7 @@ where
                             async move {
                                 let file_handle = AsyncFileDialog::new().save_file().await;
                                 file_handle
-                                    .map(|file| file.path().to_str().map(|str| str.to_string()))
-                                    .flatten()
+                                    .and_then(|file| file.path().to_str().map(|str| str.to_string()))
                             },
                             ControlMessage::CrackStart,
                         );
@@ -119,6 +214,24 @@ where
                     }
                 }
             }
+            ControlMessage::PauseButton => {
+                let (Some(cracker), Some(meta_data)) = (&self.cracker, self.crack_data.as_mut()) else {
+                    return Command::none();
+                };
+                match self.cracking {
+                    CrackerState::Running => {
+                        cracker.pause();
+                        meta_data.pause();
+                        self.cracking = CrackerState::Paused;
+                    }
+                    CrackerState::Paused => {
+                        cracker.resume();
+                        meta_data.resume();
+                        self.cracking = CrackerState::Running;
+                    }
+                    _ => {}
+                }
+            }
             ControlMessage::CrackStart(file) => self.start_crack(file),
             ControlMessage::CrackerMessage(msg) => match msg {
                 CrackerEvent::ProgressUpdate(num, results) => {
@@ -136,7 +249,10 @@ where
                         self.end_crack(true);
                     }
                 }
-                CrackerEvent::Started => self.cracking = CrackerState::Running,
+                CrackerEvent::Started(cracker) => {
+                    self.cracker = Some(cracker);
+                    self.cracking = CrackerState::Running;
+                }
                 CrackerEvent::Finished => self.end_crack(false),
             },
             ControlMessage::LoadConfig => {
@@ -149,31 +265,161 @@ where
                     ControlMessage::LoadedConfig,
                 );
             }
+            ControlMessage::WorldArea(text) => self.world_area = text,
+            ControlMessage::ImportWorld => {
+                let Some([x1, z1, x2, z2]) = self.parse_area() else {
+                    return Command::none();
+                };
+                let profile = self.tab.profile();
+                return Command::perform(
+                    async move {
+                        let handle = AsyncFileDialog::new()
+                            .pick_folder()
+                            .await
+                            .ok_or_else(|| "No folder selected".to_string())?;
+                        let world = handle.path().to_path_buf();
+                        // the imported blocks use the config format
+                        let blocks = task::spawn_blocking(move || read_nether(world, x1, z1, x2, z2, profile))
+                            .await
+                            .map_err(|err| format!("Couldnt read {}: {err}", handle.file_name()))?;
+                        if blocks.is_empty() {
+                            return Err(format!("{} has no generated nether chunks between {x1} {z1} and {x2} {z2}", handle.file_name()));
+                        }
+                        Ok(blocks.iter().map(|block| format!("{}\n", block)).collect())
+                    },
+                    ControlMessage::ImportedWorld,
+                );
+            }
+            ControlMessage::ImportedWorld(config) => match config {
+                Ok(config) => {
+                    self.import_error = None;
+                    self.tab.load_config(config);
+                }
+                Err(err) => self.import_error = Some(err),
+            },
+            ControlMessage::SchematicOrigin(text) => self.schematic_origin = text,
+            ControlMessage::ImportSchematic => {
+                let Some([x, y, z]) = self.parse_origin() else {
+                    return Command::none();
+                };
+                let profile = self.tab.profile();
+                return Command::perform(
+                    async move {
+                        let handle = AsyncFileDialog::new()
+                            .add_filter("Schematic", &["litematic", "schem"])
+                            .pick_file()
+                            .await?;
+                        let path = handle.path().to_path_buf();
+                        let blocks = task::spawn_blocking(move || read_schematic(path, x, y, z, profile)).await.ok()?;
+                        let config: String = blocks.iter().map(|block| format!("{}\n", block)).collect();
+                        Some(config).filter(|config| !config.is_empty())
+                    },
+                    ControlMessage::LoadedConfig,
+                );
+            }
             ControlMessage::LoadedConfig(config) => {
                 if let Some(config) = config {
-                    self.tab.load_config(config);
+                    if let Some(threads) = self.tab.load_config(config) {
+                        self.threads = threads.to_string();
+                    }
                 }
             }
             ControlMessage::SaveConfig => {
-                let config = self.tab.save_config();
+                let project = match self.tab.save_config(self.threads.parse().ok()) {
+                    Ok(project) => project,
+                    Err(err) => {
+                        self.save_error = Some(err);
+                        return Command::none();
+                    }
+                };
+                self.save_error = None;
+                let text = self.tab.save_text_config();
                 return Command::perform(
                     async move {
-                        if let Some(handle) = AsyncFileDialog::new().save_file().await {
-                            fs::write(handle.path(), config)
-                                .await
-                                .expect("Couldnt write contents to file");
-                        }
+                        let dialog = AsyncFileDialog::new()
+                            .add_filter("Project", &["json"])
+                            .add_filter("Block list", &["txt"])
+                            .set_file_name("bedrock.json");
+                        let Some(handle) = dialog.save_file().await else {
+                            return Ok(());
+                        };
+                        let is_text = handle.path().extension().is_some_and(|ext| ext == "txt");
+                        let config = if is_text { text } else { project };
+                        fs::write(handle.path(), config)
+                            .await
+                            .map_err(|err| format!("Couldnt write {}: {err}", handle.file_name()))
                     },
-                    |()| ControlMessage::None,
+                    ControlMessage::SavedConfig,
                 );
             }
+            ControlMessage::SavedConfig(saved) => self.save_error = saved.err(),
+            ControlMessage::CombineResults => {
+                let profile = self.tab.profile();
+                let output = self.tab.output_mode();
+                // combining keeps the roof seeds
+                let surface = (output == OutputMode::BedrockSeed).then_some(false);
+                return Command::perform(
+                    async move {
+                        let roof = read_seed_list("Roof results").await?;
+                        let floor = read_seed_list("Floor results").await?;
+                        let seeds = task::spawn_blocking(move || intersect_bedrock_seeds(&roof, &floor, profile, output)).await;
+                        let results = seeds
+                            .into_iter()
+                            .map(|(seed, mismatches)| match mismatches {
+                                0 => (seed as i64).to_string(),
+                                _ => format!("{} ({} mismatches)", seed as i64, mismatches),
+                            })
+                            .collect();
+                        Ok((results, surface))
+                    },
+                    ControlMessage::CombinedResults,
+                );
+            }
+            ControlMessage::CombinedResults(combined) => match combined {
+                Ok((results, surface)) => {
+                    self.combine_error = None;
+                    self.crack_data = Some(MetaData::combined(results, surface));
+                    self.update_checklist();
+                }
+                Err(err) => self.combine_error = Some(err),
+            },
+            ControlMessage::Benchmark => {
+                let threads = self.thread_count();
+                self.benchmarking = true;
+                return Command::perform(
+                    async move {
+                        task::spawn_blocking(move || {
+                            let calibration = calibrate(&[1, threads], BENCHMARK_DURATION);
+                            save_calibration(&calibration);
+                            calibration
+                        })
+                        .await
+                    },
+                    ControlMessage::Benchmarked,
+                );
+            }
+            ControlMessage::Benchmarked(calibration) => {
+                self.calibration = calibration;
+                self.benchmarking = false;
+            }
+            ControlMessage::Observed(i, block_type) => {
+                let (Some(distinction), Some(meta_data)) = (self.checklist.get(i), self.crack_data.as_mut()) else {
+                    return Command::none();
+                };
+                let remaining = if block_type == BlockType::BEDROCK { &distinction.bedrock } else { &distinction.other };
+                meta_data.results.retain(|result| {
+                    parse_seed_list(result).is_ok_and(|seeds| seeds.iter().all(|(seed, _)| remaining.contains(seed)))
+                });
+                meta_data.results_found = meta_data.results.len();
+                self.update_checklist();
+            }
             ControlMessage::TabMessage(msg) => self.tab.update(msg.into()),
             ControlMessage::None => {}
         }
         Command::none()
     }
 
-    fn view_control_panel(&self) -> Element<ControlMessage> {
+    fn view_control_panel(&self) -> Element<'_, ControlMessage> {
         let mut row = Row::new();
         if self.cracking == CrackerState::Idle {
             row = row.push(
@@ -185,6 +431,12 @@ where
                     .on_press(ControlMessage::CrackButton(false)),
             );
         } else {
+            let pause = match self.cracking {
+                CrackerState::Paused => button("Resume").on_press(ControlMessage::PauseButton),
+                CrackerState::Running => button("Pause").on_press(ControlMessage::PauseButton),
+                _ => button("Pause"),
+            };
+            row = row.push(pause.width(Length::Fixed(80.0)));
             row = row.push(
                 button("Stop cracking")
                     .on_press(ControlMessage::CrackButton(false))
@@ -197,11 +449,51 @@ where
             .width(Length::Fixed(70.0));
         let load_config = button("Load config").on_press(ControlMessage::LoadConfig);
         let save_config = button("Save config").on_press(ControlMessage::SaveConfig);
+        let area_input = text_input("World area x1 z1 x2 z2", &self.world_area)
+            .on_input(ControlMessage::WorldArea)
+            .width(Length::Fixed(170.0));
+        let mut import_world = button("Import world");
+        if self.parse_area().is_some() {
+            import_world = import_world.on_press(ControlMessage::ImportWorld);
+        }
+        let mut benchmark = button(if self.benchmarking { "Benchmarking..." } else { "Benchmark" });
+        if self.cracking == CrackerState::Idle && !self.benchmarking {
+            benchmark = benchmark.on_press(ControlMessage::Benchmark);
+        }
+        let mut combine_results = button("Combine results");
+        if self.cracking == CrackerState::Idle {
+            combine_results = combine_results.on_press(ControlMessage::CombineResults);
+        }
+        let origin_input = text_input("Schematic origin x y z", &self.schematic_origin)
+            .on_input(ControlMessage::SchematicOrigin)
+            .width(Length::Fixed(180.0));
+        let mut import_schematic = button("Import schematic");
+        if self.parse_origin().is_some() {
+            import_schematic = import_schematic.on_press(ControlMessage::ImportSchematic);
+        }
 
-        row = row.push(thread_input).push(load_config).push(save_config);
+        row = row
+            .push(thread_input)
+            .push(load_config)
+            .push(save_config)
+            .push(area_input)
+            .push(import_world)
+            .push(origin_input)
+            .push(import_schematic)
+            .push(combine_results)
+            .push(benchmark);
+        if let Some(err) = &self.combine_error {
+            row = row.push(text(err));
+        }
+        if let Some(err) = &self.import_error {
+            row = row.push(text(err));
+        }
+        if let Some(err) = &self.save_error {
+            row = row.push(text(err));
+        }
         if let Some(meta_data) = &self.crack_data {
             let mut progress_data = Column::new();
-            if let TimeElapsed::Running(_, percent) = meta_data.time {
+            if let TimeElapsed::Running(_, percent) | TimeElapsed::Paused(_, percent) = meta_data.time {
                 progress_data = progress_data.push(progress_bar(0.0..=1.0, percent));
             }
             row = row.push(progress_data.push(text(meta_data)));
@@ -216,25 +508,99 @@ where
             .map(ControlMessage::CrackerMessage)
     }
 
-    fn start_crack(&mut self, file: Option<String>) {
+    fn parse_origin(&self) -> Option<[i32; 3]> {
+        let coords: Vec<_> = self.schematic_origin
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|coord| !coord.is_empty())
+            .map(|coord| coord.parse::<i32>().ok())
+            .collect::<Option<_>>()?;
+        coords.try_into().ok()
+    }
+
+    /// two corners like --area, DEFAULT_WORLD_AREA if the input is empty
+    fn parse_area(&self) -> Option<[i32; 4]> {
+        if self.world_area.trim().is_empty() {
+            return Some(DEFAULT_WORLD_AREA);
+        }
+        let coords: Vec<_> = self.world_area
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|coord| !coord.is_empty())
+            .map(|coord| coord.parse::<i32>().ok())
+            .collect::<Option<_>>()?;
+        let [x1, z1, x2, z2]: [i32; 4] = coords.try_into().ok()?;
+        Some([x1.min(x2), z1.min(z2), x1.max(x2), z1.max(z2)])
+    }
+
+    /// all cores if the input is empty or invalid
+    fn thread_count(&self) -> u64 {
         match self.threads.parse::<u64>() {
-            Ok(x) if x != 0 => {}
-            _ => self.threads = num_cpus::get().to_string(),
+            Ok(x) if x != 0 => x,
+            _ => num_cpus::get() as u64,
         }
-        self.crack_data = Some(MetaData::start());
+    }
+
+    fn start_crack(&mut self, file: Option<String>) {
+        let threads = self.thread_count();
+        self.threads = threads.to_string();
+        let expected = self.tab.estimate(threads, &self.calibration).map(|estimate| estimate.seconds);
+        self.crack_data = Some(MetaData::start(expected, self.tab.seed_surface()));
+        self.checklist.clear();
         self.cracking = CrackerState::Starting(file);
     }
 
     fn end_crack(&mut self, cancelled: bool) {
+        // dropping the subscription cancels the crack, even a paused one
         self.cracking = CrackerState::Idle;
+        self.cracker = None;
         self.crack_data.as_mut().unwrap().end(cancelled);
+        self.update_checklist();
+    }
+
+    /// only for a few results that are all shown
+    fn update_checklist(&mut self) {
+        self.checklist.clear();
+        let Some(meta_data) = &self.crack_data else {
+            return;
+        };
+        if meta_data.results_found != meta_data.results.len() || meta_data.results.len() > MAX_DISTINGUISHED {
+            return;
+        }
+        if let Ok(seeds) = parse_seed_list(&meta_data.results.join("\n")) {
+            let seeds: Vec<_> = seeds.into_iter().map(|(seed, _)| seed).collect();
+            self.checklist = self.tab.distinguish(&seeds, meta_data.surface);
+        }
+    }
+}
+
+/// "seed 5", "one of 5, 6" or "one of 7 seeds"
+fn describe_seeds(seeds: &[u64]) -> String {
+    match seeds {
+        [seed] => format!("seed {}", *seed as i64),
+        seeds if seeds.len() <= 3 => {
+            let seeds: Vec<_> = seeds.iter().map(|seed| (*seed as i64).to_string()).collect();
+            format!("one of {}", seeds.join(", "))
+        }
+        seeds => format!("one of {} seeds", seeds.len()),
     }
 }
 
+/// asks for a result file of the cracker, title says which surface it should belong to
+async fn read_seed_list(title: &str) -> Result<Vec<(u64, u32)>, String> {
+    let handle = AsyncFileDialog::new()
+        .set_title(title)
+        .pick_file()
+        .await
+        .ok_or_else(|| "No file selected".to_string())?;
+    let list = fs::read_to_string(handle.path())
+        .await
+        .map_err(|err| format!("Couldnt read {}: {err}", handle.file_name()))?;
+    parse_seed_list(&list).map_err(|err| format!("{}: {err}", handle.file_name()))
+}
+
 #[derive(Debug, Clone)]
 pub enum CrackerEvent {
     ProgressUpdate(f32, Vec<String>),
-    Started,
+    Started(CancelToken),
     Finished,
 }
 
@@ -244,6 +610,7 @@ pub enum CrackerState {
     Idle,
     Starting(Option<String>),
     Running,
+    Paused,
 }
 
 #[derive(Debug, Clone)]
@@ -251,14 +618,31 @@ struct MetaData {
     results_found: usize,
     results: Vec<String>,
     time: TimeElapsed,
+    /// runtime the estimate predicted, shown until there is progress
+    expected_seconds: Option<f64>,
+    /// Some(is_floor) if the results are bedrock seeds of that surface
+    surface: Option<bool>,
 }
 
 impl MetaData {
-    fn start() -> MetaData {
+    fn start(expected_seconds: Option<f64>, surface: Option<bool>) -> MetaData {
         MetaData {
             results_found: 0,
             results: vec![],
             time: TimeElapsed::Running(Instant::now(), 0.0),
+            expected_seconds,
+            surface,
+        }
+    }
+
+    /// results of combining a roof and a floor crack, shown like the results of a crack
+    fn combined(results: Vec<String>, surface: Option<bool>) -> MetaData {
+        MetaData {
+            results_found: results.len(),
+            results: results.into_iter().take(1000).collect(),
+            time: TimeElapsed::Finished(Duration::ZERO),
+            expected_seconds: None,
+            surface,
         }
     }
 
@@ -267,26 +651,55 @@ impl MetaData {
             self.time = TimeElapsed::Cancelled
         } else if let TimeElapsed::Running(start, _) = self.time {
             self.time = TimeElapsed::Finished(start.elapsed())
+        } else if let TimeElapsed::Paused(elapsed, _) = self.time {
+            self.time = TimeElapsed::Finished(elapsed)
         }
     }
 
     fn update(&mut self, percent_to_add: f32, results_found: usize) {
         self.results_found += results_found;
+        match self.time {
+            TimeElapsed::Running(start, percentage) => {
+                self.time = TimeElapsed::Running(start, percentage + percent_to_add)
+            }
+            // threads can still finish a few checks after pausing
+            TimeElapsed::Paused(elapsed, percentage) => {
+                self.time = TimeElapsed::Paused(elapsed, percentage + percent_to_add)
+            }
+            _ => {}
+        }
+    }
+
+    fn pause(&mut self) {
         if let TimeElapsed::Running(start, percentage) = self.time {
-            self.time = TimeElapsed::Running(start, percentage + percent_to_add);
+            self.time = TimeElapsed::Paused(start.elapsed(), percentage);
+        }
+    }
+
+    /// the paused time doesnt count towards the elapsed time
+    fn resume(&mut self) {
+        if let TimeElapsed::Paused(elapsed, percentage) = self.time {
+            self.time = TimeElapsed::Running(Instant::now() - elapsed, percentage);
         }
     }
 }
 
 impl fmt::Display for MetaData {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "Found {} results {}", self.results_found, self.time)
+        write!(f, "Found {} results {}", self.results_found, self.time)?;
+        match (&self.time, self.expected_seconds) {
+            (TimeElapsed::Running(_, percentage), Some(seconds)) if *percentage == 0.0 => {
+                write!(f, " / expected: {}", format_duration(seconds))
+            }
+            _ => Ok(()),
+        }
     }
 }
 
 #[derive(Debug, Clone)]
 enum TimeElapsed {
     Running(Instant, f32),
+    Paused(Duration, f32),
     Finished(Duration),
     Cancelled,
 }
@@ -303,6 +716,9 @@ impl fmt::Display for TimeElapsed {
                     write!(f, "in {}s / eta: {}s ", elapsed, total as u64)
                 }
             }
+            TimeElapsed::Paused(elapsed, _) => {
+                write!(f, "in {}s (paused)", elapsed.as_secs())
+            }
             TimeElapsed::Finished(duration) => {
                 write!(f, "in {}s", duration.as_secs())
             }
@@ -318,13 +734,34 @@ pub trait ApplicationTab {
 
     fn new() -> Self;
 
-    fn load_config(&mut self, file: String);
+    /// accepts projects and legacy configs, returns the thread count stored in the project
+    fn load_config(&mut self, file: String) -> Option<u64>;
 
-    fn save_config(&self) -> String;
+    /// Err names the rows that cant be saved
+    fn save_config(&self, threads: Option<u64>) -> Result<String, String>;
+
+    /// the old one block per line format, the command line version reads it
+    fn save_text_config(&self) -> String;
+
+    /// the bedrock layers the importers read
+    fn profile(&self) -> BedrockProfile;
+
+    /// what combining roof and floor results produces
+    fn output_mode(&self) -> OutputMode;
+
+    /// Some(is_floor) if a crack of the tab reports bedrock seeds of that surface
+    fn seed_surface(&self) -> Option<bool>;
 
     fn update(&mut self, message: Self::Message);
 
-    fn view(&self) -> Element<TabMessage>;
+    /// threads is the amount the next crack would use
+    fn view(&self, threads: u64, calibration: &Calibration) -> Element<'_, TabMessage>;
+
+    /// positions that tell the results of a crack apart, surface is the one of MetaData
+    fn distinguish(&self, seeds: &[u64], surface: Option<bool>) -> Vec<Distinction>;
+
+    /// None if the tab cant estimate its crack
+    fn estimate(&self, threads: u64, calibration: &Calibration) -> Option<Estimate>;
 
     fn poll_cracker(&self, state: &CrackerState, threads: &str) -> Subscription<CrackerEvent>;
 }